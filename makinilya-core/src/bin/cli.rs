@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand};
-use makinilya::core::MakinilyaCore;
+use makinilya::{config::OutputFormat, core::MakinilyaCore};
 
 mod style;
 
@@ -63,6 +63,18 @@ struct NewArgs {
 struct BuildArgs {
     /// directory that contains the manifest
     path: Option<PathBuf>,
+
+    /// name of the profile to build
+    #[arg(short, long)]
+    profile: Option<String>,
+
+    /// builds every profile of the project
+    #[arg(short, long, conflicts_with = "profile")]
+    all: bool,
+
+    /// format of the manuscript, which overrides the configured one
+    #[arg(short, long, conflicts_with = "all")]
+    format: Option<OutputFormat>,
 }
 
 fn main() {
@@ -72,25 +84,31 @@ fn main() {
         SubCommands::Build(build_args) => {
             let path = build_args.path.unwrap_or("./".into());
 
-            match MakinilyaCore::build(path) {
-                Err(error) => println!("{}", error),
-                _ => (),
+            let result = match (build_args.profile, build_args.all, build_args.format) {
+                (_, true, _) => MakinilyaCore::build_all(path),
+                (profile, false, Some(format)) => {
+                    MakinilyaCore::build_as(path, profile.as_deref(), format)
+                }
+                (Some(profile), false, None) => MakinilyaCore::build_profile(path, &profile),
+                (None, false, None) => MakinilyaCore::build(path),
+            };
+
+            if let Err(error) = result {
+                println!("{}", error)
             }
         }
         SubCommands::New(new_args) => {
             let path = new_args.path.unwrap_or("./".into());
 
-            match MakinilyaCore::new(path) {
-                Err(error) => println!("{}", error),
-                _ => (),
+            if let Err(error) = MakinilyaCore::new(path) {
+                println!("{}", error)
             }
         }
         SubCommands::Check(new_args) => {
             let path = new_args.path.unwrap_or("./".into());
 
-            match MakinilyaCore::check(path) {
-                Err(error) => println!("{}", error),
-                _ => (),
+            if let Err(error) = MakinilyaCore::check(path) {
+                println!("{}", error)
             }
         }
     }
@@ -0,0 +1,1056 @@
+//! Layout-agnostic walk of the story that's shared by every output format.
+//!
+//! The builders do not traverse the [`Story`] tree themselves. Instead, the story is flattened
+//! into a sequence of [`Block`]s which each format renders with its own layout.
+
+use std::collections::HashMap;
+
+use makinilya_text::{MakinilyaText, Rule};
+
+use super::{ManuscriptBuilderLayout, NumberingLayout};
+use crate::{
+    config::{Alignment, ParagraphBreak},
+    story::{Story, StoryKind},
+};
+
+/// Heading of a part or a chapter, whose number and title are shown on separate lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub number: Option<String>,
+    pub title: Option<String>,
+}
+
+impl Heading {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            number: None,
+            title: Some(title.into()),
+        }
+    }
+
+    /// Heading of the chapter that opens the book, which is neither shown nor numbered.
+    pub fn untitled() -> Self {
+        Self {
+            number: None,
+            title: None,
+        }
+    }
+
+    /// The lines of the heading, with the number above the title.
+    pub fn lines(&self) -> Vec<&str> {
+        self.number
+            .iter()
+            .chain(self.title.iter())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The heading on a single line, e.g. `Chapter One: The Storm`.
+    pub fn text(&self) -> String {
+        self.lines().join(": ")
+    }
+}
+
+/// Image of the project that's placed on its own line, e.g.
+/// `![A map](images/map.png){width=4 align=left}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    /// The path of the image file, relative to the project directory.
+    pub path: String,
+    /// The text that describes the image, which is shown in its place by outputs without images.
+    pub alt: String,
+    pub width_inch: Option<f32>,
+    pub height_inch: Option<f32>,
+    pub alignment: Alignment,
+}
+
+impl Image {
+    /// Reads the image of a line, if it's an image. Unknown or malformed options are ignored.
+    pub fn parse(line: &str) -> Option<Self> {
+        let markup = MakinilyaText::parse_block(line)
+            .ok()?
+            .next()?
+            .into_inner()
+            .next()
+            .filter(|pair| pair.as_rule() == Rule::image)?;
+
+        let mut image = Self {
+            path: String::new(),
+            alt: String::new(),
+            width_inch: None,
+            height_inch: None,
+            alignment: Alignment::Center,
+        };
+
+        for pair in markup.into_inner() {
+            match pair.as_rule() {
+                Rule::image_alt => image.alt = pair.as_str().trim().to_string(),
+                Rule::image_path => image.path = pair.as_str().trim().to_string(),
+                Rule::image_option => {
+                    let (name, value) = pair.as_str().split_once('=')?;
+                    let inch = value.trim_end_matches("in").parse::<f32>().ok();
+                    match (name.to_lowercase().as_str(), value.to_lowercase().as_str()) {
+                        ("width", _) => image.width_inch = inch.or(image.width_inch),
+                        ("height", _) => image.height_inch = inch.or(image.height_inch),
+                        ("align", "left") => image.alignment = Alignment::Left,
+                        ("align", "center" | "centre") => image.alignment = Alignment::Center,
+                        ("align", "right") => image.alignment = Alignment::Right,
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Some(image)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    /// Heading of a nested directory of chapters.
+    PartHeading(Heading),
+    ChapterHeading(Heading),
+    /// Heading of a page of front or back matter, which starts a new page and is never
+    /// numbered. The title of a dedication or an epigraph is not shown.
+    MatterHeading {
+        title: String,
+        titled: bool,
+    },
+    Paragraph(String),
+    /// Paragraph that opens a scene right after a heading or a scene break, which is not
+    /// indented.
+    FirstParagraph(String),
+    /// Paragraph that's centred without an indent, such as a line of a dedication or a sign.
+    CenteredParagraph(String),
+    /// Paragraph of a quoted block such as a letter, which is indented from both sides without
+    /// a first-line indent.
+    BlockQuote(String),
+    Image(Image),
+    SceneBreak,
+}
+
+impl Block {
+    /// The lines of a heading that are shown, which are none for any other block.
+    pub fn heading_lines(&self) -> Vec<&str> {
+        match self {
+            Self::PartHeading(heading) | Self::ChapterHeading(heading) => heading.lines(),
+            Self::MatterHeading {
+                title,
+                titled: true,
+            } => vec![title.as_str()],
+            _ => vec![],
+        }
+    }
+}
+
+/// Inline run of a paragraph's text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Span {
+    Text(String),
+    Emphasis(String),
+    /// Note that's referenced at the span, whose text keeps its inline markup so it's parsed
+    /// into spans of its own.
+    Note(String),
+}
+
+impl Span {
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Text(text) | Self::Emphasis(text) | Self::Note(text) => text,
+        }
+    }
+
+    /// Removes the backslashes of the escaped characters of a text.
+    fn unescape(text: &str) -> String {
+        let mut unescaped = String::with_capacity(text.len());
+        let mut characters = text.chars();
+        while let Some(character) = characters.next() {
+            match character {
+                '\\' => unescaped.extend(characters.next()),
+                _ => unescaped.push(character),
+            }
+        }
+        unescaped
+    }
+
+    /// Parses the inline markup of a paragraph into its spans. Escaped characters are unescaped
+    /// and adjacent text is merged into a single span.
+    pub fn parse(text: &str) -> Vec<Span> {
+        let Some(inline) = MakinilyaText::parse_inline(text)
+            .ok()
+            .and_then(|mut pairs| pairs.next())
+        else {
+            return vec![Span::Text(text.to_string())];
+        };
+
+        let mut spans: Vec<Span> = vec![];
+
+        for pair in inline.into_inner() {
+            let span = match pair.as_rule() {
+                Rule::emphasis => {
+                    let emphasis_text = pair.into_inner().next().expect("emphasis has text");
+                    Span::Emphasis(Self::unescape(emphasis_text.as_str()))
+                }
+                Rule::note => {
+                    let note_text = pair.into_inner().next().expect("note has text");
+                    Span::Note(note_text.as_str().trim().to_string())
+                }
+                Rule::escaped_character => Span::Text(pair.as_str()[1..].to_string()),
+                // References are resolved before the paragraph is parsed, so what's left of them
+                // lacks a definition.
+                Rule::plain_text | Rule::note_reference => Span::Text(pair.as_str().to_string()),
+                _ => continue,
+            };
+
+            match (spans.last_mut(), span) {
+                (Some(Span::Text(previous)), Span::Text(text)) => previous.push_str(&text),
+                (_, span) => spans.push(span),
+            }
+        }
+
+        spans
+    }
+
+    /// The paragraph's text without its inline markup, where its notes are left out.
+    pub fn plain(text: &str) -> String {
+        Self::parse(text)
+            .iter()
+            .filter(|span| !matches!(span, Span::Note(_)))
+            .map(Span::text)
+            .collect()
+    }
+}
+
+/// Numbers the notes of a document in the order of their references, and keeps the notes that
+/// are yet to be listed. Numbers continue across chapters.
+#[derive(Debug, Default)]
+pub struct Notes {
+    count: usize,
+    pending: Vec<(usize, String)>,
+}
+
+impl Notes {
+    /// Adds the text of a note, returning its number.
+    pub fn push(&mut self, text: &str) -> usize {
+        self.count += 1;
+        self.pending.push((self.count, text.to_string()));
+        self.count
+    }
+
+    /// The number of notes that were added.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Takes the numbered notes that are yet to be listed.
+    pub fn take(&mut self) -> Vec<(usize, String)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// The paragraph's text without its inline markup, where its notes are numbered in
+    /// brackets, e.g. `[1]`.
+    pub fn plain(&mut self, text: &str) -> String {
+        Span::parse(text)
+            .iter()
+            .map(|span| match span {
+                Span::Note(note) => format!("[{}]", self.push(note)),
+                span => span.text().to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Blocks of a scene that its lines are grouped into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineGroup {
+    Text,
+    Quote,
+    Centered,
+    /// Image lines, which are each a block of their own.
+    Image,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManuscriptDocument {
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentChapter<'a> {
+    /// The heading on a single line, which is empty for an untitled chapter.
+    pub title: String,
+    /// The lines of the heading that are shown, which are none when it's untitled.
+    pub heading: Vec<&'a str>,
+    pub part: bool,
+    /// Whether the chapter is a page of front or back matter.
+    pub matter: bool,
+    pub blocks: &'a [Block],
+}
+
+impl DocumentChapter<'_> {
+    /// Whether the chapter's heading is shown.
+    pub fn titled(&self) -> bool {
+        !self.heading.is_empty()
+    }
+}
+
+impl ManuscriptDocument {
+    /// Groups the blocks by their preceding part, chapter or matter heading. Blocks before the
+    /// first heading belong to an untitled chapter.
+    pub fn chapters(&self) -> Vec<DocumentChapter<'_>> {
+        let mut chapters = vec![];
+        let mut chapter = DocumentChapter {
+            title: String::new(),
+            heading: vec![],
+            part: false,
+            matter: false,
+            blocks: &[],
+        };
+        let mut start = 0;
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let (title, heading, part, matter) = match block {
+                Block::PartHeading(heading) => (heading.text(), heading.lines(), true, false),
+                Block::ChapterHeading(heading) => (heading.text(), heading.lines(), false, false),
+                Block::MatterHeading { title, titled } => (
+                    title.clone(),
+                    match titled {
+                        true => vec![title.as_str()],
+                        false => vec![],
+                    },
+                    false,
+                    true,
+                ),
+                _ => continue,
+            };
+
+            if index > 0 {
+                chapters.push(DocumentChapter {
+                    blocks: &self.blocks[start..index],
+                    ..chapter
+                });
+            }
+            chapter = DocumentChapter {
+                title,
+                heading: heading
+                    .into_iter()
+                    .filter(|line| !line.is_empty())
+                    .collect(),
+                part,
+                matter,
+                blocks: &[],
+            };
+            start = index + 1;
+        }
+
+        if !self.blocks.is_empty() {
+            chapters.push(DocumentChapter {
+                blocks: &self.blocks[start..],
+                ..chapter
+            });
+        }
+
+        chapters
+    }
+
+    /// The images that the scenes place, in the order of their blocks.
+    pub fn images(&self) -> Vec<&Image> {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Image(image) => Some(image),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the document has any directory of chapters.
+    pub fn has_parts(&self) -> bool {
+        self.blocks
+            .iter()
+            .any(|block| matches!(block, Block::PartHeading(_)))
+    }
+
+    /// Returns the heading level of a chapter, starting at `1`. Chapters are a level below the
+    /// parts when the document has any.
+    pub fn heading_level(&self, chapter: &DocumentChapter) -> usize {
+        match !chapter.part && !chapter.matter && self.has_parts() {
+            true => 2,
+            false => 1,
+        }
+    }
+
+    /// Flattens the story between its front and back matter, and numbers its headings.
+    pub fn from_story(story: &Story, layout: &ManuscriptBuilderLayout) -> Self {
+        let mut document = Self::default();
+        for page in story.front_matter() {
+            document.push_matter(page, layout);
+        }
+        document.push_story(story, 0, layout.paragraph_break);
+        for page in story.back_matter() {
+            document.push_matter(page, layout);
+        }
+        document.number_headings(&layout.numbering);
+        document
+    }
+
+    /// Numbers the chapters and the parts in their order, where chapters are numbered
+    /// continuously across parts. The pages of matter and the untitled chapter that opens the
+    /// book are never numbered.
+    fn number_headings(&mut self, numbering: &NumberingLayout) {
+        let mut chapter_count = 0;
+        let mut part_count = 0;
+
+        for block in self.blocks.iter_mut() {
+            let (heading, heading_numbering, count) = match block {
+                Block::PartHeading(heading) => (heading, &numbering.part, &mut part_count),
+                Block::ChapterHeading(heading) => (heading, &numbering.chapter, &mut chapter_count),
+                _ => continue,
+            };
+            let Some(heading_numbering) = heading_numbering else {
+                continue;
+            };
+            if heading.title.is_none() {
+                continue;
+            }
+
+            *count += 1;
+            heading.number = Some(heading_numbering.label(*count));
+            heading.title = match numbering.titles {
+                true => heading
+                    .title
+                    .as_deref()
+                    .map(|title| Story::without_ordinal(title).to_string()),
+                false => None,
+            };
+        }
+    }
+
+    /// Replaces the references to notes in the content of a scene file with the inline notes of
+    /// their definitions, which are dropped from the content, e.g. `[^1]` with `^[A note.]` for
+    /// a `[^1]: A note.` line. A reference without a definition in the file is kept as text.
+    fn resolve_notes(content: &str) -> String {
+        let mut definitions: HashMap<&str, String> = HashMap::new();
+        let mut lines = vec![];
+
+        for line in content.lines() {
+            let definition = MakinilyaText::parse_block(line)
+                .ok()
+                .and_then(|mut pairs| pairs.next())
+                .and_then(|block| block.into_inner().next())
+                .filter(|pair| pair.as_rule() == Rule::note_definition);
+            let Some(definition) = definition else {
+                lines.push(line);
+                continue;
+            };
+
+            let mut pairs = definition.into_inner();
+            let label = pairs.next().map_or("", |label| label.as_str());
+            let text = pairs.next().map_or("", |text| text.as_str());
+            let mut note = String::with_capacity(text.len());
+            let mut characters = text.chars();
+            while let Some(character) = characters.next() {
+                match character {
+                    '\\' => {
+                        note.push('\\');
+                        note.extend(characters.next());
+                    }
+                    ']' => note.push_str("\\]"),
+                    _ => note.push(character),
+                }
+            }
+            definitions.insert(label, note);
+        }
+
+        if definitions.is_empty() {
+            return content.to_string();
+        }
+
+        lines
+            .iter()
+            .map(|line| {
+                let Some(inline) = MakinilyaText::parse_inline(line)
+                    .ok()
+                    .and_then(|mut pairs| pairs.next())
+                else {
+                    return line.to_string();
+                };
+
+                inline
+                    .into_inner()
+                    .map(|pair| {
+                        let definition = match pair.as_rule() {
+                            Rule::note_reference => pair
+                                .clone()
+                                .into_inner()
+                                .next()
+                                .and_then(|label| definitions.get(label.as_str())),
+                            _ => None,
+                        };
+                        match definition {
+                            Some(note) => format!("^[{}]", note),
+                            None => pair.as_str().to_string(),
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Pushes a page of matter, whose paragraphs are centred when it's untitled.
+    fn push_matter(&mut self, page: &Story, layout: &ManuscriptBuilderLayout) {
+        let titled = !layout
+            .untitled_matter
+            .iter()
+            .any(|title| title.to_lowercase() == page.title().to_lowercase());
+        self.blocks.push(Block::MatterHeading {
+            title: page.title().clone(),
+            titled,
+        });
+
+        for content in page.contents() {
+            let content = Self::resolve_notes(content);
+            let lines: Vec<&str> = content.lines().collect();
+            let paragraphs = Self::split_paragraphs(&lines, layout.paragraph_break);
+            for (index, paragraph) in paragraphs.into_iter().enumerate() {
+                self.blocks.push(match (titled, index) {
+                    (false, _) => Block::CenteredParagraph(paragraph),
+                    (true, 0) => Block::FirstParagraph(paragraph),
+                    (true, _) => Block::Paragraph(paragraph),
+                });
+            }
+        }
+    }
+
+    /// Splits the content of a scene file into the lines of its scenes, which are separated by
+    /// explicit scene breaks. The scenes that are left blank around a break are dropped.
+    fn split_scenes(content: &str) -> Vec<Vec<&str>> {
+        let mut scenes = vec![vec![]];
+
+        for line in content.lines() {
+            match MakinilyaText::is_scene_break(line) {
+                true => scenes.push(vec![]),
+                false => scenes.last_mut().unwrap().push(line),
+            }
+        }
+
+        if scenes.len() > 1 {
+            scenes.retain(|scene| scene.iter().any(|line| !line.trim().is_empty()));
+        }
+
+        scenes
+    }
+
+    /// Reads the lines of a scene as paragraphs with the given model. Blank lines never make a
+    /// paragraph of their own.
+    fn split_paragraphs(lines: &[&str], paragraph_break: ParagraphBreak) -> Vec<String> {
+        match paragraph_break {
+            ParagraphBreak::Line => lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.to_string())
+                .collect(),
+            ParagraphBreak::BlankLine => lines
+                .split(|line| line.trim().is_empty())
+                .filter(|paragraph| !paragraph.is_empty())
+                .map(|paragraph| {
+                    paragraph
+                        .iter()
+                        .map(|line| line.trim())
+                        .collect::<Vec<&str>>()
+                        .join(" ")
+                })
+                .collect(),
+        }
+    }
+
+    /// Groups the lines of a scene by the block that they're in. A `> ` line is quoted, and the
+    /// lines between a fence such as `::: center` and a closing `:::` are in the fence's block.
+    /// The lines of a fence with an unknown name are kept as text, and every image line is a
+    /// group of its own.
+    fn group_lines<'a>(lines: &[&'a str]) -> Vec<(LineGroup, Vec<&'a str>)> {
+        let mut groups: Vec<(LineGroup, Vec<&str>)> = vec![];
+        let mut fence: Option<LineGroup> = None;
+
+        for &line in lines {
+            let markup = MakinilyaText::parse_block(line)
+                .ok()
+                .and_then(|mut pairs| pairs.next())
+                .and_then(|block| block.into_inner().next());
+            let (group, text) = match markup {
+                Some(pair) if pair.as_rule() == Rule::fence => {
+                    fence = pair.into_inner().next().map(|name| {
+                        match name.as_str().to_lowercase().as_str() {
+                            "center" | "centre" => LineGroup::Centered,
+                            "quote" => LineGroup::Quote,
+                            _ => LineGroup::Text,
+                        }
+                    });
+                    continue;
+                }
+                Some(pair) if pair.as_rule() == Rule::image => (LineGroup::Image, line),
+                Some(pair) if pair.as_rule() == Rule::block_quote && fence.is_none() => {
+                    let text = pair.into_inner().next().map_or("", |text| text.as_str());
+                    (LineGroup::Quote, text)
+                }
+                _ => (fence.unwrap_or(LineGroup::Text), line),
+            };
+
+            match groups.last_mut() {
+                Some((last_group, group_lines))
+                    if *last_group == group && group != LineGroup::Image =>
+                {
+                    group_lines.push(text)
+                }
+                _ => groups.push((group, vec![text])),
+            }
+        }
+
+        groups
+    }
+
+    /// Pushes the paragraphs of a scene, where the first paragraph of its text is not indented.
+    /// An image before the text, such as a chapter ornament, doesn't count as its first
+    /// paragraph.
+    fn push_scene(&mut self, lines: &[&str], paragraph_break: ParagraphBreak) {
+        let mut is_first = true;
+
+        for (group, group_lines) in Self::group_lines(lines) {
+            for paragraph in Self::split_paragraphs(&group_lines, paragraph_break) {
+                self.blocks.push(match group {
+                    LineGroup::Image => match Image::parse(&paragraph) {
+                        Some(image) => Block::Image(image),
+                        None => Block::Paragraph(paragraph),
+                    },
+                    LineGroup::Text if is_first => Block::FirstParagraph(paragraph),
+                    LineGroup::Text => Block::Paragraph(paragraph),
+                    LineGroup::Quote => Block::BlockQuote(paragraph),
+                    LineGroup::Centered => Block::CenteredParagraph(paragraph),
+                });
+                is_first = is_first && group == LineGroup::Image;
+            }
+        }
+    }
+
+    /// Pushes the heading of a part or a chapter, its scenes, and then its nested stories. The
+    /// root is always the book, whose scenes open the story in an untitled chapter. Stories
+    /// without any scene are left out.
+    fn push_story(&mut self, story: &Story, depth: usize, paragraph_break: ParagraphBreak) {
+        if story.contents().is_empty() && story.parts().is_empty() {
+            return;
+        }
+
+        let kind = match depth {
+            0 => StoryKind::Book,
+            _ => story.kind(),
+        };
+        match kind {
+            StoryKind::Book if story.contents().is_empty() => (),
+            StoryKind::Book => self.blocks.push(Block::ChapterHeading(Heading::untitled())),
+            StoryKind::Part => self
+                .blocks
+                .push(Block::PartHeading(Heading::new(story.title()))),
+            StoryKind::Chapter => self
+                .blocks
+                .push(Block::ChapterHeading(Heading::new(story.title()))),
+        }
+
+        let contents: Vec<String> = story
+            .contents()
+            .iter()
+            .map(|content| Self::resolve_notes(content))
+            .collect();
+        let scenes: Vec<Vec<&str>> = contents
+            .iter()
+            .flat_map(|content| Self::split_scenes(content))
+            .collect();
+        let mut peekable_scenes = scenes.iter().peekable();
+
+        while let Some(scene) = peekable_scenes.next() {
+            self.push_scene(scene, paragraph_break);
+
+            if peekable_scenes.peek().is_some() {
+                self.blocks.push(Block::SceneBreak);
+            }
+        }
+
+        for part in story.parts() {
+            self.push_story(part, depth + 1, paragraph_break);
+        }
+    }
+}
+
+#[cfg(test)]
+mod document_tests {
+    use super::*;
+    use crate::{builder::HeadingNumbering, config::NumberStyle};
+
+    #[test]
+    fn flattens_story() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1\nLine 2");
+        chapter_1.push_content("Line 3");
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::ChapterHeading(Heading::new("Chapter 1")),
+                Block::FirstParagraph("Line 1".into()),
+                Block::Paragraph("Line 2".into()),
+                Block::SceneBreak,
+                Block::FirstParagraph("Line 3".into()),
+            ]
+        );
+
+        let chapters = document.chapters();
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "Chapter 1");
+        assert_eq!(chapters[0].blocks.len(), 4);
+    }
+
+    #[test]
+    fn splits_scenes_at_breaks() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1\n\n* * *\n\nLine 2\n***");
+        chapter_1.push_content("Line 3\n**");
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::ChapterHeading(Heading::new("Chapter 1")),
+                Block::FirstParagraph("Line 1".into()),
+                Block::SceneBreak,
+                Block::FirstParagraph("Line 2".into()),
+                Block::SceneBreak,
+                Block::FirstParagraph("Line 3".into()),
+                Block::Paragraph("**".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_paragraphs() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1\r\nwrapped\r\n\r\n\r\nLine 2\r\n");
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks[1..],
+            [
+                Block::FirstParagraph("Line 1".into()),
+                Block::Paragraph("wrapped".into()),
+                Block::Paragraph("Line 2".into()),
+            ]
+        );
+
+        let layout = ManuscriptBuilderLayout {
+            paragraph_break: ParagraphBreak::BlankLine,
+            ..Default::default()
+        };
+        let document = ManuscriptDocument::from_story(&story, &layout);
+        assert_eq!(
+            document.blocks[1..],
+            [
+                Block::FirstParagraph("Line 1 wrapped".into()),
+                Block::Paragraph("Line 2".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_blocks() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content(
+            "> Dear Tom,\n> Come home.\nShe folded it.\n::: center\nNO ENTRY\n> Keep out\n:::\n\\> Not a quote.",
+        );
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks[1..],
+            [
+                Block::BlockQuote("Dear Tom,".into()),
+                Block::BlockQuote("Come home.".into()),
+                Block::Paragraph("She folded it.".into()),
+                Block::CenteredParagraph("NO ENTRY".into()),
+                Block::CenteredParagraph("> Keep out".into()),
+                Block::Paragraph("\\> Not a quote.".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_images() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content(
+            "![An ornament](images/ornament.png){width=1.5in align=right unknown=1}\nIt began.\n![](map.png)\n![Not](an image) here.",
+        );
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks[1..],
+            [
+                Block::Image(Image {
+                    path: "images/ornament.png".into(),
+                    alt: "An ornament".into(),
+                    width_inch: Some(1.5),
+                    height_inch: None,
+                    alignment: Alignment::Right,
+                }),
+                Block::FirstParagraph("It began.".into()),
+                Block::Image(Image {
+                    path: "map.png".into(),
+                    alt: "".into(),
+                    width_inch: None,
+                    height_inch: None,
+                    alignment: Alignment::Center,
+                }),
+                Block::Paragraph("![Not](an image) here.".into()),
+            ]
+        );
+        assert_eq!(document.images().len(), 2);
+    }
+
+    #[test]
+    fn parses_spans() {
+        assert_eq!(
+            Span::parse("I have *never \\* once* been, 2 * 3 \\*"),
+            vec![
+                Span::Text("I have ".into()),
+                Span::Emphasis("never * once".into()),
+                Span::Text(" been, 2 * 3 *".into()),
+            ]
+        );
+        assert_eq!(Span::plain("*a* b"), "a b");
+        assert_eq!(
+            Span::parse("Tea^[Or *coffee* \\]] [^1]"),
+            vec![
+                Span::Text("Tea".into()),
+                Span::Note("Or *coffee* \\]".into()),
+                Span::Text(" [^1]".into()),
+            ]
+        );
+        assert_eq!(Span::plain("Tea^[Or coffee]."), "Tea.");
+
+        let mut notes = Notes::default();
+        assert_eq!(notes.plain("A^[B] c^[D]"), "A[1] c[2]");
+        assert_eq!(notes.take(), vec![(1, "B".into()), (2, "D".into())]);
+        assert_eq!(notes.plain("E^[F]"), "E[3]");
+    }
+
+    #[test]
+    fn resolves_notes() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1
+            .push_content("Tea[^tea] and cake[^2].\n\n[^tea]: Or *coffee* [sic].\nMore tea[^tea].");
+        story.push_part(chapter_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks[1..],
+            [
+                Block::FirstParagraph("Tea^[Or *coffee* [sic\\].] and cake[^2].".into()),
+                Block::Paragraph("More tea^[Or *coffee* [sic\\].].".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn heads_parts() {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1");
+        part_1.push_part(chapter_1);
+        story.push_part(part_1);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::PartHeading(Heading::new("Part 1")),
+                Block::ChapterHeading(Heading::new("Chapter 1")),
+                Block::FirstParagraph("Line 1".into()),
+            ]
+        );
+
+        let chapters = document.chapters();
+        assert_eq!(chapters.len(), 2);
+        assert!(chapters[0].part);
+        assert!(chapters[0].blocks.is_empty());
+        assert!(!chapters[1].part);
+        assert_eq!(document.heading_level(&chapters[0]), 1);
+        assert_eq!(document.heading_level(&chapters[1]), 2);
+    }
+
+    #[test]
+    fn surrounds_story_with_matter() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1");
+        story.push_part(chapter_1);
+        let mut dedication = Story::new("Dedication");
+        dedication.push_content("For Mark");
+        story.push_front_matter(dedication);
+        let mut acknowledgments = Story::new("Acknowledgments");
+        acknowledgments.push_content("Thanks");
+        story.push_back_matter(acknowledgments);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::MatterHeading {
+                    title: "Dedication".into(),
+                    titled: false
+                },
+                Block::CenteredParagraph("For Mark".into()),
+                Block::ChapterHeading(Heading::new("Chapter 1")),
+                Block::FirstParagraph("Line 1".into()),
+                Block::MatterHeading {
+                    title: "Acknowledgments".into(),
+                    titled: true
+                },
+                Block::FirstParagraph("Thanks".into()),
+            ]
+        );
+
+        let chapters = document.chapters();
+        assert_eq!(chapters.len(), 3);
+        assert!(chapters[0].matter && !chapters[0].titled());
+        assert!(!chapters[1].matter && chapters[1].titled());
+        assert!(chapters[2].matter && chapters[2].titled());
+    }
+
+    #[test]
+    fn opens_book_and_parts_with_scenes() {
+        let mut story = Story::new("Root");
+        story.push_content("Prologue");
+        let mut part_1 = Story::new("Part 1");
+        part_1.push_content("Epigraph");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Line 1");
+        part_1.push_part(chapter_1);
+        story.push_part(part_1);
+        story.push_part(Story::new("Empty"));
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::ChapterHeading(Heading::untitled()),
+                Block::FirstParagraph("Prologue".into()),
+                Block::PartHeading(Heading::new("Part 1")),
+                Block::FirstParagraph("Epigraph".into()),
+                Block::ChapterHeading(Heading::new("Chapter 1")),
+                Block::FirstParagraph("Line 1".into()),
+            ]
+        );
+        assert!(!document.chapters()[0].titled());
+    }
+
+    #[test]
+    fn numbers_headings() {
+        let mut story = Story::new("Root");
+        for part_title in ["01 - Beginning", "02 - End"] {
+            let mut part = Story::new(part_title);
+            let mut chapter = Story::new("Storm");
+            chapter.push_content("Line");
+            part.push_part(chapter);
+            story.push_part(part);
+        }
+        let numbering = NumberingLayout {
+            chapter: Some(HeadingNumbering {
+                template: "CHAPTER {NUMBER}".into(),
+                style: NumberStyle::Words,
+            }),
+            part: Some(HeadingNumbering {
+                template: "Part {number}".into(),
+                style: NumberStyle::Words,
+            }),
+            titles: true,
+        };
+
+        let layout = ManuscriptBuilderLayout {
+            numbering: numbering.clone(),
+            ..Default::default()
+        };
+        let document = ManuscriptDocument::from_story(&story, &layout);
+        let headings: Vec<String> = document
+            .chapters()
+            .iter()
+            .map(|chapter| chapter.title.clone())
+            .collect();
+        assert_eq!(
+            headings,
+            vec![
+                "Part One: Beginning",
+                "CHAPTER ONE: Storm",
+                "Part Two: End",
+                "CHAPTER TWO: Storm"
+            ]
+        );
+
+        let layout = ManuscriptBuilderLayout {
+            numbering: NumberingLayout {
+                titles: false,
+                ..numbering
+            },
+            ..Default::default()
+        };
+        let document = ManuscriptDocument::from_story(&story, &layout);
+        assert_eq!(
+            document.blocks[1],
+            Block::ChapterHeading(Heading {
+                number: Some("CHAPTER ONE".into()),
+                title: None
+            })
+        );
+    }
+
+    #[test]
+    fn centres_configured_untitled_matter() {
+        let mut story = Story::new("Root");
+        let mut dedication = Story::new("Dedication");
+        dedication.push_content("For Mark");
+        story.push_front_matter(dedication);
+        let mut dedicace = Story::new("Dédicace");
+        dedicace.push_content("Pour Marc");
+        story.push_front_matter(dedicace);
+
+        let layout = ManuscriptBuilderLayout {
+            untitled_matter: vec!["DÉDICACE".into()],
+            ..Default::default()
+        };
+        let document = ManuscriptDocument::from_story(&story, &layout);
+        assert_eq!(
+            document.blocks,
+            vec![
+                Block::MatterHeading {
+                    title: "Dedication".into(),
+                    titled: true
+                },
+                Block::FirstParagraph("For Mark".into()),
+                Block::MatterHeading {
+                    title: "Dédicace".into(),
+                    titled: false
+                },
+                Block::CenteredParagraph("Pour Marc".into()),
+            ]
+        );
+    }
+}
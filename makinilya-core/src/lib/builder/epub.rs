@@ -0,0 +1,549 @@
+//! EPUB 3 output of the manuscript.
+//!
+//! Every chapter of the story is written to its own XHTML document, which are listed in both the
+//! navigation document and the spine of the package. The publication is packed with the `zip`
+//! crate, with the uncompressed `mimetype` entry first as the specification requires.
+//!
+//! Notes are referenced with `noteref` links, so reading systems could show them as pop-ups.
+//! Footnotes are asides at the end of their chapter's document, and endnotes are listed in a
+//! document of their own after the chapters. The images of the scenes are bundled as
+//! `images/image-1.png` and so on, in the order that they're first placed.
+
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+    time::SystemTime,
+};
+
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use super::{
+    document::{Block, DocumentChapter, ManuscriptDocument, Notes},
+    images::{image_extension, image_media_type},
+    BuilderError, ManuscriptBuilder, ManuscriptBuilderLayout,
+};
+use crate::{
+    config::NotePlacement,
+    extensions::{EscapeXml, Slugify, ToIso8601},
+    files::File,
+    story::Story,
+};
+
+/// Files that are bundled with the publication alongside the story.
+#[derive(Debug, Default)]
+pub struct EpubAssets {
+    pub cover: Option<File>,
+    pub stylesheet: Option<String>,
+}
+
+const DEFAULT_STYLESHEET: &str = r#"body {
+  font-family: serif;
+  line-height: 1.5;
+  margin: 0 5%;
+}
+
+h1 {
+  font-weight: normal;
+  margin: 3em 0 2em;
+  text-align: center;
+}
+
+p {
+  margin: 0;
+  text-align: justify;
+  text-indent: 1.5em;
+}
+
+h1 + p,
+p.scene-break + p {
+  text-indent: 0;
+}
+
+p.scene-break {
+  margin: 1em 0;
+  text-align: center;
+  text-indent: 0;
+}
+
+p.centered {
+  text-align: center;
+  text-indent: 0;
+}
+
+p.block-quote {
+  margin: 0 2em;
+  text-indent: 0;
+}
+
+aside p,
+.notes p {
+  font-size: 0.875em;
+  text-indent: 0;
+}
+
+.title-page {
+  margin-top: 30%;
+  text-align: center;
+}
+
+.title-page p {
+  text-align: center;
+  text-indent: 0;
+}
+
+.cover {
+  margin: 0;
+  text-align: center;
+}
+
+.cover img {
+  max-height: 100%;
+  max-width: 100%;
+}
+
+figure.image {
+  margin: 1em 0;
+}
+
+figure.image img {
+  max-width: 100%;
+}
+"#;
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+struct EpubItem {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: Option<&'static str>,
+    in_spine: bool,
+}
+
+impl ManuscriptBuilder {
+    fn epub_identifier(&self) -> String {
+        format!(
+            "urn:makinilya:{}",
+            format!("{} {}", self.layout.pen_name, self.layout.title).slugify()
+        )
+    }
+
+    fn xhtml_document(&self, title: &str, body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{language}" lang="{language}">
+<head>
+  <meta charset="UTF-8"/>
+  <title>{title}</title>
+  <link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+            language = self.layout.language.escape_xml(),
+            title = title.escape_xml(),
+            body = body
+        )
+    }
+
+    fn epub_note_reference(&self, number: usize) -> String {
+        let href = match self.layout.notes {
+            NotePlacement::Footnotes => "",
+            NotePlacement::Endnotes => "notes.xhtml",
+        };
+        format!(
+            "<a epub:type=\"noteref\" href=\"{1}#note-{0}\" id=\"note-reference-{0}\"><sup>{0}</sup></a>",
+            number, href
+        )
+    }
+
+    /// Lists the endnotes, where each links back to its reference in the chapter at `href`.
+    fn epub_endnotes(&self, endnotes: &[(String, Vec<(usize, String)>)]) -> String {
+        let items: String = endnotes
+            .iter()
+            .flat_map(|(href, notes)| notes.iter().map(move |note| (href, note)))
+            .map(|(href, (number, note))| {
+                format!(
+                    "      <li epub:type=\"endnote\" id=\"note-{0}\"><p>{2} <a href=\"{1}#note-reference-{0}\">&#8617;</a></p></li>\n",
+                    number,
+                    href,
+                    Self::html_note_text(note)
+                )
+            })
+            .collect();
+        let title = ManuscriptBuilderLayout::DEFAULT_NOTES_TITLE;
+        let body = format!(
+            "  <section class=\"notes\" epub:type=\"endnotes\">\n    <h1>{}</h1>\n    <ol>\n{}    </ol>\n  </section>\n",
+            title, items
+        );
+        self.xhtml_document(title, &body)
+    }
+
+    /// Builds the document of a chapter, whose images are linked through `image_hrefs`, which
+    /// maps their paths in the project to their paths in the publication.
+    fn epub_chapter(
+        &self,
+        chapter: &DocumentChapter,
+        notes: &mut Notes,
+        image_hrefs: &HashMap<String, String>,
+    ) -> String {
+        let note_reference = |number: usize| self.epub_note_reference(number);
+        let mut body = match chapter.titled() {
+            true => format!("  <h1>{}</h1>\n", Self::html_heading(chapter)),
+            false => String::new(),
+        };
+
+        for block in chapter.blocks {
+            match block {
+                // Headings start their own chapters.
+                Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {}
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                    if text.trim().is_empty() => {}
+                Block::Paragraph(text) | Block::FirstParagraph(text) => {
+                    body.push_str(&format!(
+                        "  <p>{}</p>\n",
+                        Self::html_inline(text, notes, &note_reference)
+                    ));
+                }
+                Block::CenteredParagraph(text) => {
+                    body.push_str(&format!(
+                        "  <p class=\"centered\">{}</p>\n",
+                        Self::html_inline(text, notes, &note_reference)
+                    ));
+                }
+                Block::BlockQuote(text) => {
+                    body.push_str(&format!(
+                        "  <p class=\"block-quote\">{}</p>\n",
+                        Self::html_inline(text, notes, &note_reference)
+                    ));
+                }
+                Block::Image(image) => {
+                    let href = image_hrefs.get(&image.path).unwrap_or(&image.path);
+                    body.push_str(&format!("  {}\n", Self::html_image(image, href)));
+                }
+                Block::SceneBreak => {
+                    body.push_str(&format!(
+                        "  <p class=\"scene-break\">{}</p>\n",
+                        Self::html_scene_break(&self.layout.scene_break)
+                    ));
+                }
+            }
+        }
+
+        if self.layout.notes == NotePlacement::Footnotes {
+            for (number, note) in notes.take() {
+                body.push_str(&format!(
+                    "  <aside epub:type=\"footnote\" id=\"note-{0}\">\n    <p><a href=\"#note-reference-{0}\">{0}.</a> {1}</p>\n  </aside>\n",
+                    number,
+                    Self::html_note_text(&note)
+                ));
+            }
+        }
+
+        self.xhtml_document(&chapter.title, &body)
+    }
+
+    fn epub_title_page(&self) -> String {
+        let byline = match self.layout.pen_name.is_empty() {
+            true => String::new(),
+            false => format!("    <p>{}</p>\n", self.layout.pen_name.escape_xml()),
+        };
+        let body = format!(
+            "  <section class=\"title-page\" epub:type=\"titlepage\">\n    <h1>{}</h1>\n{}  </section>\n",
+            self.layout.title.escape_xml(),
+            byline
+        );
+        self.xhtml_document(&self.layout.title, &body)
+    }
+
+    fn epub_navigation(&self, document: &ManuscriptDocument) -> String {
+        let title = self
+            .layout
+            .table_of_contents
+            .as_deref()
+            .unwrap_or(ManuscriptBuilderLayout::DEFAULT_TABLE_OF_CONTENTS_TITLE);
+        let body = format!(
+            "  <nav epub:type=\"toc\" id=\"toc\">\n    <h1>{}</h1>\n{}  </nav>\n",
+            title.escape_xml(),
+            Self::html_contents(document, |index| format!("chapter-{}.xhtml", index + 1))
+        );
+        self.xhtml_document(title, &body)
+    }
+
+    fn epub_package(&self, items: &[EpubItem], modified: &str) -> String {
+        let manifest: String = items
+            .iter()
+            .map(|item| {
+                format!(
+                    "    <item id=\"{}\" href=\"{}\" media-type=\"{}\"{}/>\n",
+                    item.id,
+                    item.href.escape_xml(),
+                    item.media_type,
+                    item.properties.map_or(String::new(), |properties| format!(
+                        " properties=\"{}\"",
+                        properties
+                    ))
+                )
+            })
+            .collect();
+        let spine: String = items
+            .iter()
+            .filter(|item| item.in_spine)
+            .map(|item| format!("    <itemref idref=\"{}\"/>\n", item.id))
+            .collect();
+        let cover_meta = match items.iter().any(|item| item.id == "cover-image") {
+            true => "    <meta name=\"cover\" content=\"cover-image\"/>\n",
+            false => "",
+        };
+        let creator = match self.layout.pen_name.is_empty() {
+            true => String::new(),
+            false => format!(
+                "    <dc:creator>{}</dc:creator>\n",
+                self.layout.pen_name.escape_xml()
+            ),
+        };
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{language}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+{creator}    <dc:language>{language}</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+{cover_meta}  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine>
+{spine}  </spine>
+</package>
+"#,
+            language = self.layout.language.escape_xml(),
+            identifier = self.epub_identifier().escape_xml(),
+            title = self.layout.title.escape_xml(),
+            creator = creator,
+            modified = modified,
+            cover_meta = cover_meta,
+            manifest = manifest,
+            spine = spine
+        )
+    }
+
+    pub fn build_epub(&self, story: &Story, assets: &EpubAssets) -> Result<Vec<u8>, BuilderError> {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let chapters = document.chapters();
+
+        let mut files: Vec<(String, Vec<u8>)> = vec![];
+        let mut items: Vec<EpubItem> = vec![];
+
+        if let Some(cover) = &assets.cover {
+            let extension = cover.extension.clone().unwrap_or("jpg".into());
+            let image_href = format!("images/cover.{}", extension);
+            let body = format!(
+                "  <section class=\"cover\" epub:type=\"cover\">\n    <img src=\"{}\" alt=\"{}\"/>\n  </section>\n",
+                image_href,
+                self.layout.title.escape_xml()
+            );
+
+            files.push((image_href.clone(), cover.content.clone()));
+            items.push(EpubItem {
+                id: "cover-image".into(),
+                href: image_href,
+                media_type: image_media_type(cover.extension.as_deref()).into(),
+                properties: Some("cover-image"),
+                in_spine: false,
+            });
+
+            files.push((
+                "cover.xhtml".into(),
+                self.xhtml_document(&self.layout.title, &body).into_bytes(),
+            ));
+            items.push(EpubItem {
+                id: "cover".into(),
+                href: "cover.xhtml".into(),
+                media_type: "application/xhtml+xml".into(),
+                properties: None,
+                in_spine: true,
+            });
+        }
+
+        files.push(("title.xhtml".into(), self.epub_title_page().into_bytes()));
+        items.push(EpubItem {
+            id: "title-page".into(),
+            href: "title.xhtml".into(),
+            media_type: "application/xhtml+xml".into(),
+            properties: None,
+            in_spine: true,
+        });
+
+        files.push((
+            "nav.xhtml".into(),
+            self.epub_navigation(&document).into_bytes(),
+        ));
+        items.push(EpubItem {
+            id: "nav".into(),
+            href: "nav.xhtml".into(),
+            media_type: "application/xhtml+xml".into(),
+            properties: Some("nav"),
+            in_spine: true,
+        });
+
+        let mut image_hrefs: HashMap<String, String> = HashMap::new();
+        for image in document.images() {
+            if image_hrefs.contains_key(&image.path) {
+                continue;
+            }
+
+            let number = image_hrefs.len() + 1;
+            let extension = image_extension(&image.path);
+            let href = match extension {
+                Some(extension) => format!("images/image-{}.{}", number, extension),
+                None => format!("images/image-{}", number),
+            };
+            files.push((href.clone(), self.image_content(image)?.to_vec()));
+            items.push(EpubItem {
+                id: format!("image-{}", number),
+                href: href.clone(),
+                media_type: image_media_type(extension).into(),
+                properties: None,
+                in_spine: false,
+            });
+            image_hrefs.insert(image.path.clone(), href);
+        }
+
+        let mut notes = Notes::default();
+        let mut endnotes = vec![];
+        for (index, chapter) in chapters.iter().enumerate() {
+            let href = format!("chapter-{}.xhtml", index + 1);
+            files.push((
+                href.clone(),
+                self.epub_chapter(chapter, &mut notes, &image_hrefs)
+                    .into_bytes(),
+            ));
+            endnotes.push((href.clone(), notes.take()));
+            items.push(EpubItem {
+                id: format!("chapter-{}", index + 1),
+                href,
+                media_type: "application/xhtml+xml".into(),
+                properties: None,
+                in_spine: true,
+            });
+        }
+
+        if notes.count() > 0 && self.layout.notes == NotePlacement::Endnotes {
+            files.push((
+                "notes.xhtml".into(),
+                self.epub_endnotes(&endnotes).into_bytes(),
+            ));
+            items.push(EpubItem {
+                id: "notes".into(),
+                href: "notes.xhtml".into(),
+                media_type: "application/xhtml+xml".into(),
+                properties: None,
+                in_spine: true,
+            });
+        }
+
+        let stylesheet = assets.stylesheet.as_deref().unwrap_or(DEFAULT_STYLESHEET);
+        files.push(("style.css".into(), stylesheet.as_bytes().to_vec()));
+        items.push(EpubItem {
+            id: "style".into(),
+            href: "style.css".into(),
+            media_type: "text/css".into(),
+            properties: None,
+            in_spine: false,
+        });
+
+        let package = self.epub_package(&items, &SystemTime::now().to_iso8601());
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/epub+zip")?;
+        zip.start_file("META-INF/container.xml", deflated)?;
+        zip.write_all(CONTAINER.as_bytes())?;
+        zip.start_file("OEBPS/content.opf", deflated)?;
+        zip.write_all(package.as_bytes())?;
+
+        for (path, content) in files {
+            zip.start_file(format!("OEBPS/{}", path), deflated)?;
+            zip.write_all(&content)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod epub_tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+
+    #[test]
+    fn builds_epub() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter <1>");
+        chapter_1.push_content("I am Scene #1.\n![A map](maps/map.png){width=4}");
+        chapter_1.push_content("I am Scene #2.^[Or #3.]");
+        story.push_part(chapter_1);
+
+        let mut builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        assert!(builder.build_epub(&story, &EpubAssets::default()).is_err());
+        builder
+            .images
+            .insert("maps/map.png".into(), b"png".to_vec());
+        let epub = builder.build_epub(&story, &EpubAssets::default()).unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(epub)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+
+        let mut chapter = String::new();
+        archive
+            .by_name("OEBPS/chapter-1.xhtml")
+            .unwrap()
+            .read_to_string(&mut chapter)
+            .unwrap();
+        assert!(chapter.contains("<h1>Chapter &lt;1&gt;</h1>"));
+        assert!(chapter.contains("<p class=\"scene-break\">#</p>"));
+        assert!(chapter.contains("<p>I am Scene #2.<a epub:type=\"noteref\" href=\"#note-1\" id=\"note-reference-1\"><sup>1</sup></a></p>"));
+        assert!(chapter.contains("<aside epub:type=\"footnote\" id=\"note-1\">"));
+        assert!(chapter.contains("<figure class=\"image\" style=\"text-align: center\"><img src=\"images/image-1.png\" alt=\"A map\" style=\"width: 4in;\"/></figure>"));
+        assert!(archive.by_name("OEBPS/notes.xhtml").is_err());
+        assert!(archive.by_name("OEBPS/images/image-1.png").is_ok());
+
+        let mut package = String::new();
+        archive
+            .by_name("OEBPS/content.opf")
+            .unwrap()
+            .read_to_string(&mut package)
+            .unwrap();
+        assert!(package.contains("<itemref idref=\"chapter-1\"/>"));
+        assert!(package.contains("properties=\"nav\""));
+        assert!(package.contains(
+            "<item id=\"image-1\" href=\"images/image-1.png\" media-type=\"image/png\"/>"
+        ));
+
+        let mut navigation = String::new();
+        archive
+            .by_name("OEBPS/nav.xhtml")
+            .unwrap()
+            .read_to_string(&mut navigation)
+            .unwrap();
+        assert!(navigation.contains("<li><a href=\"chapter-1.xhtml\">Chapter &lt;1&gt;</a></li>"));
+    }
+}
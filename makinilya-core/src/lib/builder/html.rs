@@ -0,0 +1,418 @@
+//! Standalone HTML output of the interpolated story.
+//!
+//! The page carries its own stylesheet and embeds its images, so it has no external resources
+//! and could be posted or opened as is. The title and pen name head the page, followed by an optional table of contents
+//! and a section per chapter. Footnotes are listed at the end of their chapter's section, and
+//! endnotes in a section of their own at the end of the page.
+
+use super::{
+    document::{Block, DocumentChapter, ManuscriptDocument, Notes, Span},
+    ManuscriptBuilder, ManuscriptBuilderLayout,
+};
+use crate::{config::NotePlacement, extensions::EscapeXml, story::Story};
+
+const HTML_STYLESHEET: &str = r#"body {
+  font-family: Georgia, "Times New Roman", serif;
+  line-height: 1.6;
+  margin: 0 auto;
+  max-width: 40em;
+  padding: 2em 1em;
+}
+
+header {
+  margin-bottom: 4em;
+  text-align: center;
+}
+
+h2,
+h3 {
+  font-weight: normal;
+  margin: 3em 0 1.5em;
+  text-align: center;
+}
+
+p {
+  margin: 0;
+  text-indent: 1.5em;
+}
+
+nav ol {
+  list-style: none;
+}
+
+header p,
+h2 + p,
+h3 + p,
+p.scene-break + p {
+  text-indent: 0;
+}
+
+p.scene-break {
+  margin: 1em 0;
+  text-align: center;
+  text-indent: 0;
+}
+
+p.centered {
+  text-align: center;
+  text-indent: 0;
+}
+
+p.block-quote {
+  margin: 0 2em;
+  text-indent: 0;
+}
+
+.notes {
+  font-size: 0.875em;
+  margin-top: 2em;
+}
+
+aside.notes {
+  border-top: 1px solid;
+}
+
+figure.image {
+  margin: 1em 0;
+}
+
+figure.image img {
+  max-width: 100%;
+}
+"#;
+
+impl ManuscriptBuilder {
+    /// Escapes the text of a paragraph, with its emphasis wrapped in `<em>` elements. Its notes
+    /// are numbered, and their references are built from their numbers by `note_reference`.
+    pub(crate) fn html_inline(
+        text: &str,
+        notes: &mut Notes,
+        note_reference: &dyn Fn(usize) -> String,
+    ) -> String {
+        Span::parse(text)
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) => text.escape_xml(),
+                Span::Emphasis(text) => format!("<em>{}</em>", text.escape_xml()),
+                Span::Note(note) => note_reference(notes.push(note)),
+            })
+            .collect()
+    }
+
+    /// The text of a note, whose inline markup is escaped like a paragraph's.
+    pub(crate) fn html_note_text(note: &str) -> String {
+        Self::html_inline(note, &mut Notes::default(), &|_| String::new())
+    }
+
+    fn html_note_reference(number: usize) -> String {
+        format!(
+            "<sup id=\"note-reference-{0}\"><a href=\"#note-{0}\">{0}</a></sup>",
+            number
+        )
+    }
+
+    /// Lists the notes that are yet to be listed, where each links back to its reference.
+    fn html_notes(notes: &mut Notes) -> String {
+        let notes = notes.take();
+        let Some((first_number, _)) = notes.first() else {
+            return String::new();
+        };
+
+        let items: String = notes
+            .iter()
+            .map(|(number, note)| {
+                format!(
+                    "<li id=\"note-{0}\">{1} <a href=\"#note-reference-{0}\">&#8617;</a></li>\n",
+                    number,
+                    Self::html_note_text(note)
+                )
+            })
+            .collect();
+        format!("<ol start=\"{}\">\n{}</ol>\n", first_number, items)
+    }
+
+    /// Escapes the text of a scene break, where a blank break keeps its line with a non-breaking
+    /// space.
+    pub(crate) fn html_scene_break(scene_break: &str) -> String {
+        match scene_break.trim().is_empty() {
+            true => String::from("&#160;"),
+            false => scene_break.escape_xml(),
+        }
+    }
+
+    /// Escapes the lines of a chapter's heading, which are separated by line breaks.
+    pub(crate) fn html_heading(chapter: &DocumentChapter) -> String {
+        chapter
+            .heading
+            .iter()
+            .map(|line| line.escape_xml())
+            .collect::<Vec<String>>()
+            .join("<br/>")
+    }
+
+    /// Builds the list of links to the titled chapters, where the chapters of a part are nested
+    /// within the part's item. A chapter is linked through its index in
+    /// [`ManuscriptDocument::chapters`].
+    pub(crate) fn html_contents(
+        document: &ManuscriptDocument,
+        href: impl Fn(usize) -> String,
+    ) -> String {
+        let mut list = String::from("<ol>\n");
+        let mut is_part_open = false;
+        let mut is_nested_list_open = false;
+
+        for (index, chapter) in document.chapters().iter().enumerate() {
+            if !chapter.titled() {
+                continue;
+            }
+
+            let link = format!(
+                "<a href=\"{}\">{}</a>",
+                href(index),
+                chapter.title.escape_xml()
+            );
+
+            if document.heading_level(chapter) == 1 {
+                if is_nested_list_open {
+                    list.push_str("</ol>\n");
+                    is_nested_list_open = false;
+                }
+                if is_part_open {
+                    list.push_str("</li>\n");
+                }
+                list.push_str(&format!("<li>{}", link));
+                is_part_open = true;
+
+                if !chapter.part {
+                    list.push_str("</li>\n");
+                    is_part_open = false;
+                }
+                continue;
+            }
+
+            if is_part_open && !is_nested_list_open {
+                list.push_str("\n<ol>\n");
+                is_nested_list_open = true;
+            }
+            list.push_str(&format!("<li>{}</li>\n", link));
+        }
+
+        if is_nested_list_open {
+            list.push_str("</ol>\n");
+        }
+        if is_part_open {
+            list.push_str("</li>\n");
+        }
+
+        list.push_str("</ol>\n");
+        list
+    }
+
+    fn html_chapter(
+        &self,
+        document: &ManuscriptDocument,
+        chapter: &DocumentChapter,
+        index: usize,
+        notes: &mut Notes,
+    ) -> String {
+        let mut section = format!("<section id=\"chapter-{}\">\n", index + 1);
+
+        if chapter.titled() {
+            let level = document.heading_level(chapter) + 1;
+            section.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                Self::html_heading(chapter),
+                level = level
+            ));
+        }
+
+        for block in chapter.blocks {
+            match block {
+                // Headings start their own chapters.
+                Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {}
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                    if text.trim().is_empty() => {}
+                Block::Paragraph(text) | Block::FirstParagraph(text) => section.push_str(&format!(
+                    "<p>{}</p>\n",
+                    Self::html_inline(text, notes, &Self::html_note_reference)
+                )),
+                Block::CenteredParagraph(text) => section.push_str(&format!(
+                    "<p class=\"centered\">{}</p>\n",
+                    Self::html_inline(text, notes, &Self::html_note_reference)
+                )),
+                Block::BlockQuote(text) => section.push_str(&format!(
+                    "<p class=\"block-quote\">{}</p>\n",
+                    Self::html_inline(text, notes, &Self::html_note_reference)
+                )),
+                Block::Image(image) => section.push_str(&format!(
+                    "{}\n",
+                    Self::html_image(image, &self.html_image_source(image))
+                )),
+                Block::SceneBreak => section.push_str(&format!(
+                    "<p class=\"scene-break\">{}</p>\n",
+                    Self::html_scene_break(&self.layout.scene_break)
+                )),
+            }
+        }
+
+        if self.layout.notes == NotePlacement::Footnotes {
+            let footnotes = Self::html_notes(notes);
+            if !footnotes.is_empty() {
+                section.push_str(&format!("<aside class=\"notes\">\n{}</aside>\n", footnotes));
+            }
+        }
+
+        section.push_str("</section>\n");
+        section
+    }
+
+    pub fn build_html(&self, story: &Story) -> String {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let mut notes = Notes::default();
+        let mut sections: String = document
+            .chapters()
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| self.html_chapter(&document, chapter, index, &mut notes))
+            .collect();
+        let endnotes = Self::html_notes(&mut notes);
+        if !endnotes.is_empty() {
+            sections.push_str(&format!(
+                "<section class=\"notes\" id=\"notes\">\n<h2>{}</h2>\n{}</section>\n",
+                ManuscriptBuilderLayout::DEFAULT_NOTES_TITLE,
+                endnotes
+            ));
+        }
+        let contents = match &self.layout.table_of_contents {
+            Some(title) => format!(
+                "<nav>\n<h2>{}</h2>\n{}</nav>\n",
+                title.escape_xml(),
+                Self::html_contents(&document, |index| format!("#chapter-{}", index + 1))
+            ),
+            None => String::new(),
+        };
+        let byline = match self.layout.pen_name.is_empty() {
+            true => String::new(),
+            false => format!("<p>{}</p>\n", self.layout.pen_name.escape_xml()),
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="{language}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+{stylesheet}</style>
+</head>
+<body>
+<header>
+<h1>{title}</h1>
+{byline}</header>
+{contents}{sections}</body>
+</html>
+"#,
+            language = self.layout.language.escape_xml(),
+            title = self.layout.title.escape_xml(),
+            byline = byline,
+            stylesheet = HTML_STYLESHEET,
+            contents = contents,
+            sections = sections
+        )
+    }
+}
+
+#[cfg(test)]
+mod html_tests {
+    use super::*;
+
+    #[test]
+    fn builds_html() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Tom & *Jerry*");
+        chapter_1.push_content("I am Scene #2.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let html = builder.build_html(&story);
+
+        assert!(html.contains("<h2>Chapter 1</h2>\n<p>Tom &amp; <em>Jerry</em></p>\n"));
+        assert!(html.contains("<p class=\"scene-break\">#</p>\n<p>I am Scene #2.</p>\n"));
+        assert!(!html.contains("<nav>"));
+    }
+
+    #[test]
+    fn builds_blank_scene_breaks() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am Scene #1.\n***\nI am Scene #2.");
+        story.push_part(chapter_1);
+
+        let layout = ManuscriptBuilderLayout {
+            scene_break: "".into(),
+            ..Default::default()
+        };
+        let html = ManuscriptBuilder::new(layout).build_html(&story);
+
+        assert!(html.contains(
+            "<p>I am Scene #1.</p>\n<p class=\"scene-break\">&#160;</p>\n<p>I am Scene #2.</p>\n"
+        ));
+    }
+
+    #[test]
+    fn builds_html_notes() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Tea[^1] for two.\n[^1]: Or *coffee*.");
+        story.push_part(chapter_1);
+        let mut chapter_2 = Story::new("Chapter 2");
+        chapter_2.push_content("Cake^[Or pie.]");
+        story.push_part(chapter_2);
+
+        let html = ManuscriptBuilder::new(ManuscriptBuilderLayout::default()).build_html(&story);
+        assert!(html.contains(
+            "<p>Tea<sup id=\"note-reference-1\"><a href=\"#note-1\">1</a></sup> for two.</p>\n<aside class=\"notes\">\n<ol start=\"1\">\n<li id=\"note-1\">Or <em>coffee</em>. <a href=\"#note-reference-1\">&#8617;</a></li>\n</ol>\n</aside>\n</section>\n"
+        ));
+        assert!(html.contains("<ol start=\"2\">\n<li id=\"note-2\">Or pie."));
+
+        let layout = ManuscriptBuilderLayout {
+            notes: NotePlacement::Endnotes,
+            ..Default::default()
+        };
+        let html = ManuscriptBuilder::new(layout).build_html(&story);
+        assert!(!html.contains("<aside"));
+        assert!(html.contains(
+            "<section class=\"notes\" id=\"notes\">\n<h2>Notes</h2>\n<ol start=\"1\">\n<li id=\"note-1\">"
+        ));
+    }
+
+    #[test]
+    fn builds_html_contents() {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am Scene #1.");
+        part_1.push_part(chapter_1);
+        story.push_part(part_1);
+        let mut part_2 = Story::new("Part 2");
+        let mut chapter_2 = Story::new("Chapter 2");
+        chapter_2.push_content("I am Scene #2.");
+        part_2.push_part(chapter_2);
+        story.push_part(part_2);
+
+        let layout = ManuscriptBuilderLayout {
+            table_of_contents: Some("Contents".into()),
+            ..Default::default()
+        };
+        let html = ManuscriptBuilder::new(layout).build_html(&story);
+
+        assert!(html.contains(
+            "<nav>\n<h2>Contents</h2>\n<ol>\n<li><a href=\"#chapter-1\">Part 1</a>\n<ol>\n<li><a href=\"#chapter-2\">Chapter 1</a></li>\n</ol>\n</li>\n<li><a href=\"#chapter-3\">Part 2</a>\n<ol>\n<li><a href=\"#chapter-4\">Chapter 2</a></li>\n</ol>\n</li>\n</ol>\n</nav>\n"
+        ));
+        assert!(html.contains("<section id=\"chapter-2\">\n<h3>Chapter 1</h3>\n"));
+    }
+}
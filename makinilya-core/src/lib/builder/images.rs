@@ -0,0 +1,214 @@
+//! Images that the scenes place on their own lines.
+//!
+//! The image files are read from the project before the manuscript is built, and are looked up
+//! by their path in [`ManuscriptBuilder::images`]. An image is as large as its file at 96 pixels
+//! per inch unless its width or height is given, where the other side keeps the image's
+//! proportions. It never overflows the width of the page's content.
+
+use std::path::Path;
+
+use base64::encode as encode_base64;
+use docx_rs::{Paragraph, Pic, Run};
+use image::GenericImageView;
+
+use super::{document::Image, styles::CENTERED_STYLE, BuilderError, ManuscriptBuilder};
+use crate::{config::Alignment, extensions::EscapeXml, units::Emu};
+
+const PIXELS_PER_INCH: f32 = 96.0;
+
+/// The media type of an image file from its extension, which defaults to JPEG.
+pub(crate) fn image_media_type(extension: Option<&str>) -> &'static str {
+    match extension
+        .map(|extension| extension.to_lowercase())
+        .as_deref()
+    {
+        Some("png") => "image/png",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        _ => "image/jpeg",
+    }
+}
+
+/// The extension of an image's path, if it has any.
+pub(crate) fn image_extension(path: &str) -> Option<&str> {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+}
+
+impl ManuscriptBuilder {
+    /// The content of an image's file.
+    pub(crate) fn image_content(&self, image: &Image) -> Result<&[u8], BuilderError> {
+        self.images
+            .get(&image.path)
+            .map(Vec::as_slice)
+            .ok_or_else(|| BuilderError::MissingImage(image.path.clone()))
+    }
+
+    /// The width and height of an image in inches.
+    fn image_size_inch(&self, image: &Image) -> Result<(f32, f32), BuilderError> {
+        let (pixel_width, pixel_height) = image::load_from_memory(self.image_content(image)?)
+            .map_err(|_| BuilderError::InvalidImage(image.path.clone()))?
+            .dimensions();
+        if pixel_width == 0 || pixel_height == 0 {
+            return Err(BuilderError::InvalidImage(image.path.clone()));
+        }
+
+        let ratio = pixel_height as f32 / pixel_width as f32;
+        let (width, height) = match (image.width_inch, image.height_inch) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (width, width * ratio),
+            (None, Some(height)) => (height / ratio, height),
+            (None, None) => (
+                pixel_width as f32 / PIXELS_PER_INCH,
+                pixel_height as f32 / PIXELS_PER_INCH,
+            ),
+        };
+
+        let content_width = self.layout.page.content_width_inch();
+        Ok(match width > content_width {
+            true => (content_width, height * content_width / width),
+            false => (width, height),
+        })
+    }
+
+    /// Builds the paragraph of an image, which is embedded inline with its alignment.
+    pub(crate) fn docx_image_paragraph(&self, image: &Image) -> Result<Paragraph, BuilderError> {
+        let (width, height) = self.image_size_inch(image)?;
+        let picture = Pic::new(self.image_content(image)?)
+            .size(Emu::from_inch(width).into(), Emu::from_inch(height).into());
+
+        Ok(Paragraph::new()
+            .style(CENTERED_STYLE)
+            .align(image.alignment.into())
+            .add_run(Run::new().add_image(picture)))
+    }
+
+    /// Builds the figure of an image whose source is `source`. Its size is only set when it's
+    /// given, since the browser already keeps the proportions of an image.
+    pub(crate) fn html_image(image: &Image, source: &str) -> String {
+        let alignment = match image.alignment {
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+        };
+        let size: String = [("width", image.width_inch), ("height", image.height_inch)]
+            .iter()
+            .filter_map(|(property, inch)| inch.map(|inch| format!("{}: {}in; ", property, inch)))
+            .collect();
+        let style = match size.is_empty() {
+            true => String::new(),
+            false => format!(" style=\"{}\"", size.trim_end()),
+        };
+
+        format!(
+            "<figure class=\"image\" style=\"text-align: {}\"><img src=\"{}\" alt=\"{}\"{}/></figure>",
+            alignment,
+            source.escape_xml(),
+            image.alt.escape_xml(),
+            style
+        )
+    }
+
+    /// The source of an image in a standalone page, which embeds the image's content. An image
+    /// that wasn't read is linked by its path instead.
+    pub(crate) fn html_image_source(&self, image: &Image) -> String {
+        match self.image_content(image) {
+            Ok(content) => format!(
+                "data:{};base64,{}",
+                image_media_type(image_extension(&image.path)),
+                encode_base64(content)
+            ),
+            Err(_) => image.path.clone(),
+        }
+    }
+
+    /// The text that's placed instead of an image by outputs without images, which is its
+    /// description in brackets, or nothing when it has none.
+    pub(crate) fn image_placeholder(image: &Image) -> Option<String> {
+        match image.alt.is_empty() {
+            true => None,
+            false => Some(format!("[{}]", image.alt)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod images_tests {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageOutputFormat};
+
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut content = Cursor::new(vec![]);
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut content, ImageOutputFormat::Png)
+            .unwrap();
+        content.into_inner()
+    }
+
+    fn image(width_inch: Option<f32>, height_inch: Option<f32>) -> Image {
+        Image {
+            path: "map.png".into(),
+            alt: "A map".into(),
+            width_inch,
+            height_inch,
+            alignment: Alignment::Center,
+        }
+    }
+
+    #[test]
+    fn sizes_images() {
+        let mut builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        assert!(matches!(
+            builder.image_size_inch(&image(None, None)),
+            Err(BuilderError::MissingImage(_))
+        ));
+
+        builder
+            .images
+            .insert("map.png".into(), b"not an image".to_vec());
+        assert!(matches!(
+            builder.image_size_inch(&image(None, None)),
+            Err(BuilderError::InvalidImage(_))
+        ));
+
+        builder.images.insert("map.png".into(), png(192, 96));
+        assert_eq!(
+            builder.image_size_inch(&image(None, None)).unwrap(),
+            (2.0, 1.0)
+        );
+        assert_eq!(
+            builder.image_size_inch(&image(Some(4.0), None)).unwrap(),
+            (4.0, 2.0)
+        );
+        assert_eq!(
+            builder.image_size_inch(&image(None, Some(2.0))).unwrap(),
+            (4.0, 2.0)
+        );
+        assert_eq!(
+            builder.image_size_inch(&image(Some(13.0), None)).unwrap(),
+            (6.5, 3.25)
+        );
+    }
+
+    #[test]
+    fn builds_html_images() {
+        assert_eq!(
+            ManuscriptBuilder::html_image(&image(Some(4.0), None), "map.png"),
+            "<figure class=\"image\" style=\"text-align: center\"><img src=\"map.png\" alt=\"A map\" style=\"width: 4in;\"/></figure>"
+        );
+
+        let mut builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        assert_eq!(builder.html_image_source(&image(None, None)), "map.png");
+        builder.images.insert("map.png".into(), b"png".to_vec());
+        assert_eq!(
+            builder.html_image_source(&image(None, None)),
+            "data:image/png;base64,cG5n"
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! LaTeX source output of the manuscript for typeset proofs.
+//!
+//! The source is written from a template, which is either the default one or a file of the
+//! project. The template's placeholders are replaced with the escaped details of the manuscript:
+//!
+//! - `$class$` and `$class_options$` - the document class and its options
+//! - `$geometry$` - the page size and margins as options of the `geometry` package
+//! - `$title$`, `$author$` and `$language$` - the title, pen name and language of the story
+//! - `$scene_break$` - the text of a scene break
+//! - `$notes_preamble$` - the packages of the notes, which is `\usepackage{endnotes}` when the
+//!   notes are endnotes
+//! - `$body$` - the parts and chapters of the story
+//!
+//! Scene breaks are written as a `\scenebreak` command, which the template has to define. Notes
+//! are written as `\footnote` or `\endnote` commands, where the endnotes are printed with
+//! `\theendnotes` at the end of the body. Images are written with the `\includegraphics` command
+//! of the `graphicx` package, which the default template loads.
+
+use super::{
+    document::{Block, Image, ManuscriptDocument, Span},
+    ManuscriptBuilder,
+};
+use crate::{
+    config::{Alignment, NotePlacement},
+    story::Story,
+};
+
+const DEFAULT_LATEX_TEMPLATE: &str = r#"\documentclass[$class_options$]{$class$}
+\usepackage[T1]{fontenc}
+\usepackage[utf8]{inputenc}
+\usepackage[$geometry$]{geometry}
+\usepackage{graphicx}
+$notes_preamble$
+
+\newcommand{\scenebreak}{\par\bigskip{\centering $scene_break$\par}\bigskip}
+
+\title{$title$}
+\author{$author$}
+\date{}
+
+\begin{document}
+
+\maketitle
+
+$body$
+\end{document}
+"#;
+
+/// Document classes without chapters, where chapters are written as sections instead.
+const ARTICLE_CLASSES: [&str; 4] = ["article", "extarticle", "scrartcl", "amsart"];
+
+/// Escapes the characters that LaTeX reserves for its commands.
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            _ if character.is_control() && character != '\t' => (),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+impl ManuscriptBuilder {
+    /// Writes an unnumbered heading whose lines are separated by line breaks, and which is
+    /// listed in the table of contents on a single line.
+    fn latex_heading(command: &str, lines: &[&str]) -> String {
+        let escaped_lines: Vec<String> = lines.iter().map(|line| escape_latex(line)).collect();
+        format!(
+            "\\{command}*{{{title}}}\n\\addcontentsline{{toc}}{{{command}}}{{{contents_title}}}\n\n",
+            command = command,
+            title = escaped_lines.join("\\protect\\\\ "),
+            contents_title = escaped_lines.join(": ")
+        )
+    }
+
+    fn latex_inline(&self, text: &str) -> String {
+        Span::parse(text.trim())
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) => escape_latex(text),
+                Span::Emphasis(text) => format!("\\emph{{{}}}", escape_latex(text)),
+                Span::Note(note) => format!(
+                    "\\{}{{{}}}",
+                    match self.layout.notes {
+                        NotePlacement::Footnotes => "footnote",
+                        NotePlacement::Endnotes => "endnote",
+                    },
+                    self.latex_inline(note)
+                ),
+            })
+            .collect()
+    }
+
+    /// Writes an image with `\includegraphics`, where the image is found relative to the
+    /// project directory.
+    fn latex_image(image: &Image) -> String {
+        let size: Vec<String> = [("width", image.width_inch), ("height", image.height_inch)]
+            .iter()
+            .filter_map(|(key, inch)| inch.map(|inch| format!("{}={}in", key, inch)))
+            .collect();
+        let environment = match image.alignment {
+            Alignment::Left => "flushleft",
+            Alignment::Center => "center",
+            Alignment::Right => "flushright",
+        };
+        format!(
+            "\\begin{{{0}}}\n\\includegraphics[{1}]{{{2}}}\n\\end{{{0}}}\n\n",
+            environment,
+            size.join(","),
+            image.path
+        )
+    }
+
+    fn latex_body(&self, story: &Story) -> String {
+        let chapter_command = match ARTICLE_CLASSES.contains(&self.layout.latex.class.as_str()) {
+            true => "section",
+            false => "chapter",
+        };
+        let mut body = String::new();
+
+        for block in &ManuscriptDocument::from_story(story, &self.layout).blocks {
+            match block {
+                Block::PartHeading(heading) => {
+                    body.push_str(&Self::latex_heading("part", &heading.lines()))
+                }
+                Block::ChapterHeading(heading) if heading.lines().is_empty() => {
+                    body.push_str("\\clearpage\n\n")
+                }
+                Block::ChapterHeading(_) | Block::MatterHeading { titled: true, .. } => body
+                    .push_str(&Self::latex_heading(
+                        chapter_command,
+                        &block.heading_lines(),
+                    )),
+                Block::MatterHeading { titled: false, .. } => body.push_str(
+                    "\\clearpage\n\\thispagestyle{empty}\n\\vspace*{0.3\\textheight}\n\n",
+                ),
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                    if text.trim().is_empty() => {}
+                Block::Paragraph(text) => {
+                    body.push_str(&self.latex_inline(text));
+                    body.push_str("\n\n");
+                }
+                Block::FirstParagraph(text) => {
+                    body.push_str("\\noindent ");
+                    body.push_str(&self.latex_inline(text));
+                    body.push_str("\n\n");
+                }
+                Block::CenteredParagraph(text) => body.push_str(&format!(
+                    "\\begin{{center}}\n{}\n\\end{{center}}\n\n",
+                    self.latex_inline(text)
+                )),
+                Block::BlockQuote(text) => body.push_str(&format!(
+                    "\\begin{{quote}}\n{}\n\\end{{quote}}\n\n",
+                    self.latex_inline(text)
+                )),
+                Block::Image(image) => body.push_str(&Self::latex_image(image)),
+                Block::SceneBreak => body.push_str("\\scenebreak\n\n"),
+            }
+        }
+
+        if self.layout.notes == NotePlacement::Endnotes && body.contains("\\endnote{") {
+            body.push_str("\\theendnotes\n\n");
+        }
+
+        body
+    }
+
+    pub fn build_latex(&self, story: &Story, template: Option<&str>) -> String {
+        let page = &self.layout.page;
+        let geometry = format!(
+            "paperwidth={}in,paperheight={}in,top={}in,bottom={}in,left={}in,right={}in",
+            page.width_inch,
+            page.height_inch,
+            page.margin_top_inch,
+            page.margin_bottom_inch,
+            page.margin_left_inch,
+            page.margin_right_inch
+        );
+        let notes_preamble = match self.layout.notes {
+            NotePlacement::Footnotes => "",
+            NotePlacement::Endnotes => "\\usepackage{endnotes}",
+        };
+        let class_options = match self.layout.latex.class_options.is_empty() {
+            true => format!("{}pt", self.layout.font_size_point.round()),
+            false => self.layout.latex.class_options.join(","),
+        };
+
+        template
+            .unwrap_or(DEFAULT_LATEX_TEMPLATE)
+            .replace("$class$", &self.layout.latex.class)
+            .replace("$class_options$", &class_options)
+            .replace("$geometry$", &geometry)
+            .replace("$title$", &escape_latex(&self.layout.title))
+            .replace("$author$", &escape_latex(&self.layout.pen_name))
+            .replace("$language$", &self.layout.language)
+            .replace("$scene_break$", &escape_latex(&self.layout.scene_break))
+            .replace("$notes_preamble$", notes_preamble)
+            .replace("$body$", &self.latex_body(story))
+    }
+}
+
+#[cfg(test)]
+mod latex_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn escapes_latex() {
+        assert_eq!(
+            escape_latex("50% of $5 & #1_a {b} \\ ^ ~"),
+            "50\\% of \\$5 \\& \\#1\\_a \\{b\\} \\textbackslash{} \\textasciicircum{} \\textasciitilde{}"
+        );
+    }
+
+    #[test]
+    fn builds_latex() {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        let mut chapter_1 = Story::new("Chapter #1");
+        chapter_1.push_content("I have *never* been to the sea.");
+        chapter_1.push_content("I am Scene #2.^[Or #3.]");
+        part_1.push_part(chapter_1);
+        story.push_part(part_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let latex = builder.build_latex(&story, None);
+
+        assert!(latex.starts_with("\\documentclass[12pt]{book}"));
+        assert!(latex.contains("\\centering \\#\\par"));
+        assert!(latex.contains("\\part*{Part 1}"));
+        assert!(latex.contains(
+            "\\chapter*{Chapter \\#1}\n\\addcontentsline{toc}{chapter}{Chapter \\#1}\n\n\\noindent I have \\emph{never} been to the sea.\n\n\\scenebreak\n\n\\noindent I am Scene \\#2.\\footnote{Or \\#3.}\n\n"
+        ));
+        assert!(!latex.contains("endnotes"));
+
+        let latex = builder.build_latex(&story, Some("$title$: $body$"));
+        assert!(latex.starts_with("Untitled: \\part*"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Markdown output of the interpolated story.
+//!
+//! The story is either written as a single file, or as a file per chapter that's named after its
+//! position and title, e.g. `02-chapter-2.md`. Parts are level 1 headings and their chapters are
+//! level 2 headings. Scenes are separated by the configured scene break.
+//!
+//! Notes are written as Markdown footnotes, e.g. `[^1]`, whose definitions end their chapter so
+//! that every file of a chapter is complete. Renderers list them at the end of the document
+//! either way.
+
+use super::{
+    document::{Block, DocumentChapter, ManuscriptDocument, Notes, Span},
+    ManuscriptBuilder,
+};
+use crate::{extensions::Slugify, story::Story};
+
+/// A chapter of the story that's written to its own Markdown file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkdownFile {
+    pub file_name: String,
+    pub content: String,
+}
+
+impl ManuscriptBuilder {
+    /// Escapes the characters that Markdown would otherwise read as inline formatting.
+    fn escape_markdown_characters(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+
+        for character in text.chars() {
+            if matches!(character, '\\' | '`' | '*' | '_' | '[' | ']' | '<') {
+                escaped.push('\\');
+            }
+            escaped.push(character);
+        }
+
+        escaped
+    }
+
+    /// Escapes the characters that Markdown would otherwise read as formatting, including the
+    /// markers of headings or lists at the start of a paragraph.
+    fn escape_markdown(text: &str) -> String {
+        let text = text.trim_start();
+        let digits = text.chars().take_while(char::is_ascii_digit).count();
+
+        if text.starts_with(['#', '>', '+', '-', '=', '|']) {
+            format!("\\{}", Self::escape_markdown_characters(text))
+        } else if digits > 0 && text[digits..].starts_with(['.', ')']) {
+            format!(
+                "{}\\{}",
+                &text[..digits],
+                Self::escape_markdown_characters(&text[digits..])
+            )
+        } else {
+            Self::escape_markdown_characters(text)
+        }
+    }
+
+    /// Escapes the text of a paragraph, with its emphasis wrapped in asterisks and its notes
+    /// referenced by their numbers.
+    fn markdown_inline(text: &str, notes: &mut Notes) -> String {
+        Span::parse(text.trim_start())
+            .iter()
+            .enumerate()
+            .map(|(index, span)| match span {
+                Span::Text(text) if index == 0 => Self::escape_markdown(text),
+                Span::Text(text) => Self::escape_markdown_characters(text),
+                Span::Emphasis(text) => format!("*{}*", Self::escape_markdown_characters(text)),
+                Span::Note(note) => format!("[^{}]", notes.push(note)),
+            })
+            .collect()
+    }
+
+    fn markdown_chapter(
+        &self,
+        document: &ManuscriptDocument,
+        chapter: &DocumentChapter,
+        notes: &mut Notes,
+    ) -> String {
+        let mut sections = vec![];
+
+        if chapter.titled() {
+            sections.push(format!(
+                "{} {}",
+                "#".repeat(document.heading_level(chapter)),
+                Self::escape_markdown(&chapter.heading.join(": "))
+            ));
+        }
+
+        for block in chapter.blocks {
+            match block {
+                // Headings start their own chapters.
+                Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {}
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                    if text.trim().is_empty() => {}
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text) => {
+                    sections.push(Self::markdown_inline(text, notes))
+                }
+                Block::BlockQuote(text) => {
+                    sections.push(format!("> {}", Self::markdown_inline(text, notes)))
+                }
+                Block::Image(image) => sections.push(format!(
+                    "![{}]({})",
+                    Self::escape_markdown(&image.alt),
+                    image.path
+                )),
+                // A blank break would otherwise collapse into the paragraph separator.
+                Block::SceneBreak if self.layout.scene_break.trim().is_empty() => {
+                    sections.push(String::from("&nbsp;"))
+                }
+                Block::SceneBreak => sections.push(Self::escape_markdown(&self.layout.scene_break)),
+            }
+        }
+
+        for (number, note) in notes.take() {
+            sections.push(format!(
+                "[^{}]: {}",
+                number,
+                Self::markdown_inline(&note, &mut Notes::default())
+            ));
+        }
+
+        sections.join("\n\n") + "\n"
+    }
+
+    pub fn build_markdown(&self, story: &Story) -> String {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let mut notes = Notes::default();
+
+        document
+            .chapters()
+            .iter()
+            .map(|chapter| self.markdown_chapter(&document, chapter, &mut notes))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn build_markdown_chapters(&self, story: &Story) -> Vec<MarkdownFile> {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let chapters = document.chapters();
+        let width = chapters.len().to_string().len().max(2);
+        let mut notes = Notes::default();
+
+        chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| {
+                let slug = chapter.title.slugify();
+                let file_name = match slug.is_empty() {
+                    true => format!("{:0width$}.md", index + 1),
+                    false => format!("{:0width$}-{}.md", index + 1, slug),
+                };
+
+                MarkdownFile {
+                    file_name,
+                    content: self.markdown_chapter(&document, chapter, &mut notes),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod markdown_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    fn mock_story() -> Story {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am *Scene* #1, 2 \\* 3.\n\n1. Not a list.");
+        chapter_1.push_content("# Not a heading.^[A *note* [sic\\].]");
+        part_1.push_part(chapter_1);
+        story.push_part(part_1);
+        story
+    }
+
+    #[test]
+    fn builds_markdown() {
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+
+        assert_eq!(
+            builder.build_markdown(&mock_story()),
+            "# Part 1\n\n## Chapter 1\n\nI am *Scene* #1, 2 \\* 3.\n\n1\\. Not a list.\n\n\\#\n\n\\# Not a heading.[^1]\n\n[^1]: A *note* \\[sic\\].\n"
+        );
+
+        let files = builder.build_markdown_chapters(&mock_story());
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].file_name, "01-part-1.md");
+        assert_eq!(files[0].content, "# Part 1\n");
+        assert_eq!(files[1].file_name, "02-chapter-1.md");
+    }
+}
@@ -0,0 +1,1391 @@
+#![doc(hidden)]
+
+pub mod document;
+pub mod epub;
+pub mod html;
+pub mod images;
+pub mod latex;
+pub mod markdown;
+pub mod notes;
+pub mod odt;
+pub mod package;
+pub mod pagination;
+pub mod pdf;
+pub mod properties;
+pub mod rtf;
+pub mod screenplay;
+pub mod styles;
+pub mod text;
+
+use std::{collections::HashMap, time::SystemTime};
+
+use docx_rs::{
+    AlignmentType, Docx, FieldCharType, Footer, Header, InstrPAGE, InstrText, LineSpacing,
+    LineSpacingType, PageMargin, Paragraph, Run, RunFonts, SpecialIndentType, Tab, TabValueType,
+    Table, TableCell, TableOfContents, TableRow, VAlignType, WidthType,
+};
+use thiserror::Error;
+
+use self::{
+    document::{Block, ManuscriptDocument, Notes, Span},
+    package::DocxPackage,
+    styles::{
+        BLOCK_QUOTE_STYLE, CENTERED_STYLE, FIRST_PARAGRAPH_STYLE, HEADING_1_STYLE, HEADING_2_STYLE,
+        NORMAL_STYLE, SCENE_BREAK_STYLE, TITLE_STYLE, TOC_HEADING_STYLE,
+    },
+};
+use crate::{
+    config::{
+        Alignment, ChapterStart, Config, ContactInformation, LayoutConfig, NotePlacement,
+        NumberStyle, PageSize, ParagraphBreak, RunningHeadConfig, StandardPageSize,
+        TitlePageVariant, WordCountRounding,
+    },
+    extensions::{CloneOnSome, ToIso8601, WithThousandsSeparator},
+    story::Story,
+    units::{HalfPoint, Twip},
+    word_count::WordCount,
+};
+
+#[derive(Error, Debug)]
+pub enum BuilderError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Zipper(#[from] zip::result::ZipError),
+
+    #[error(transparent)]
+    Xml(#[from] xml::reader::Error),
+
+    #[error("Screenplays could only be built as `docx`, not as `{0}`.")]
+    UnsupportedScreenplayFormat(String),
+
+    #[error("Character `{0}` is in neither the standard nor the bundled fonts of the PDF.")]
+    UnencodableCharacter(char),
+
+    #[error(transparent)]
+    FontSubset(#[from] subsetter::Error),
+
+    #[error("Image `{0}` was not read from the project.")]
+    MissingImage(String),
+
+    #[error("Image `{0}` could not be decoded.")]
+    InvalidImage(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ParagraphLayout {
+    pub font_family: String,
+    pub font_size_point: f32,
+    pub line_spacing_point: f32,
+    pub after_line_spacing_point: f32,
+    pub first_line_indention_inch: f32,
+    pub left_indention_inch: f32,
+    pub right_indention_inch: f32,
+    pub alignment: AlignmentType,
+}
+
+impl Default for ParagraphLayout {
+    fn default() -> Self {
+        Self {
+            font_family: ManuscriptBuilderLayout::DEFAULT_FONT_FAMILY.into(),
+            font_size_point: 12.0,
+            line_spacing_point: 24.0,
+            after_line_spacing_point: 0.0,
+            first_line_indention_inch: 0.0,
+            left_indention_inch: 0.0,
+            right_indention_inch: 0.0,
+            alignment: AlignmentType::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PageLayout {
+    pub width_inch: f32,
+    pub height_inch: f32,
+    pub margin_top_inch: f32,
+    pub margin_bottom_inch: f32,
+    pub margin_left_inch: f32,
+    pub margin_right_inch: f32,
+}
+
+impl PageLayout {
+    pub const DEFAULT_MARGIN_INCH: f32 = 1.0;
+
+    pub fn content_width_inch(&self) -> f32 {
+        self.width_inch - self.margin_left_inch - self.margin_right_inch
+    }
+
+    pub fn content_height_inch(&self) -> f32 {
+        self.height_inch - self.margin_top_inch - self.margin_bottom_inch
+    }
+}
+
+impl Default for PageLayout {
+    fn default() -> Self {
+        let (width_inch, height_inch) =
+            PageSize::Standard(StandardPageSize::Letter).dimensions_inch();
+        Self {
+            width_inch,
+            height_inch,
+            margin_top_inch: Self::DEFAULT_MARGIN_INCH,
+            margin_bottom_inch: Self::DEFAULT_MARGIN_INCH,
+            margin_left_inch: Self::DEFAULT_MARGIN_INCH,
+            margin_right_inch: Self::DEFAULT_MARGIN_INCH,
+        }
+    }
+}
+
+impl From<&LayoutConfig> for PageLayout {
+    fn from(value: &LayoutConfig) -> Self {
+        let default = Self::default();
+        let (width_inch, height_inch) = value
+            .page_size
+            .map_or((default.width_inch, default.height_inch), |page_size| {
+                page_size.dimensions_inch()
+            });
+        let margins = value.margins.as_ref();
+
+        Self {
+            width_inch,
+            height_inch,
+            margin_top_inch: margins
+                .and_then(|margins| margins.top)
+                .unwrap_or(default.margin_top_inch),
+            margin_bottom_inch: margins
+                .and_then(|margins| margins.bottom)
+                .unwrap_or(default.margin_bottom_inch),
+            margin_left_inch: margins
+                .and_then(|margins| margins.left)
+                .unwrap_or(default.margin_left_inch),
+            margin_right_inch: margins
+                .and_then(|margins| margins.right)
+                .unwrap_or(default.margin_right_inch),
+        }
+    }
+}
+
+impl From<Alignment> for AlignmentType {
+    fn from(value: Alignment) -> Self {
+        match value {
+            Alignment::Left => AlignmentType::Left,
+            Alignment::Center => AlignmentType::Center,
+            Alignment::Right => AlignmentType::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunningHeadSegment {
+    Text(String),
+    PageNumber,
+    PageCount,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunningHeadLayout {
+    pub template: String,
+    pub alignment: AlignmentType,
+    pub first_page: bool,
+}
+
+impl RunningHeadLayout {
+    pub const DEFAULT_HEADER_TEMPLATE: &'static str = "{surname} / {short_title} / {page}";
+    /// The default header of an anonymous manuscript, which leaves out the author's surname.
+    pub const DEFAULT_ANONYMOUS_HEADER_TEMPLATE: &'static str = "{short_title} / {page}";
+
+    fn from_config(
+        value: Option<&RunningHeadConfig>,
+        default_template: Option<&str>,
+    ) -> Option<Self> {
+        let template = value
+            .and_then(|config| config.text.clone())
+            .or(default_template.map(String::from))?;
+
+        if template.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            template,
+            alignment: value
+                .and_then(|config| config.alignment)
+                .map_or(AlignmentType::Right, AlignmentType::from),
+            first_page: value.and_then(|config| config.first_page).unwrap_or(false),
+        })
+    }
+
+    /// Splits the template into literal text and page fields, replacing every other field with
+    /// its value from the builder layout. Unknown fields are kept as they are.
+    pub fn segments(&self, layout: &ManuscriptBuilderLayout) -> Vec<RunningHeadSegment> {
+        let mut segments = vec![];
+        let mut text = String::new();
+        let mut characters = self.template.chars();
+
+        while let Some(character) = characters.next() {
+            if character != '{' {
+                text.push(character);
+                continue;
+            }
+
+            let field: String = characters.by_ref().take_while(|c| *c != '}').collect();
+            let segment = match field.as_str() {
+                "page" => RunningHeadSegment::PageNumber,
+                "pages" => RunningHeadSegment::PageCount,
+                "title" => RunningHeadSegment::Text(layout.title.clone()),
+                "short_title" => RunningHeadSegment::Text(layout.short_title.clone()),
+                "pen_name" => RunningHeadSegment::Text(layout.pen_name.clone()),
+                "surname" => RunningHeadSegment::Text(layout.surname()),
+                _ => RunningHeadSegment::Text(format!("{{{}}}", field)),
+            };
+
+            match segment {
+                RunningHeadSegment::Text(value) => text.push_str(&value),
+                field_segment => {
+                    if !text.is_empty() {
+                        segments.push(RunningHeadSegment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(field_segment);
+                }
+            }
+        }
+
+        if !text.is_empty() {
+            segments.push(RunningHeadSegment::Text(text));
+        }
+
+        segments
+    }
+}
+
+/// Blocks of the title page, laid out with exact line spacing where the middle block is centered
+/// on the second third of the page and the bottom block is flush with the bottom margin. The
+/// middle block of a short story starts a third of the way down instead, and the story follows
+/// it on the same page.
+#[derive(Debug, Clone)]
+pub struct TitlePageLayout {
+    pub top_lines: Vec<String>,
+    /// Line that's flush right on the first line of the top block, across from its first line.
+    pub top_right_line: Option<String>,
+    pub middle_lines: Vec<String>,
+    pub bottom_lines: Vec<String>,
+    pub top_layout: ParagraphLayout,
+    pub middle_layout: ParagraphLayout,
+    pub bottom_layout: ParagraphLayout,
+    pub middle_space_before_point: f32,
+    pub bottom_space_before_point: f32,
+}
+
+impl TitlePageLayout {
+    /// The lines of the top block, where the first line is paired with the flush right line.
+    /// The flush right line has a line of its own when the top block is otherwise empty.
+    pub fn top_rows(&self) -> Vec<(&str, Option<&str>)> {
+        let mut rows: Vec<(&str, Option<&str>)> = self
+            .top_lines
+            .iter()
+            .map(|line| (line.as_str(), None))
+            .collect();
+        if let Some(right_line) = &self.top_right_line {
+            match rows.first_mut() {
+                Some(row) => row.1 = Some(right_line),
+                None => rows.push(("", Some(right_line))),
+            }
+        }
+
+        rows
+    }
+}
+
+/// Numbering of the headings of either chapters or parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadingNumbering {
+    pub template: String,
+    pub style: NumberStyle,
+}
+
+impl HeadingNumbering {
+    fn from_config(template: Option<&String>, style: Option<NumberStyle>) -> Option<Self> {
+        template.map(|template| Self {
+            template: template.clone(),
+            style: style.unwrap_or_default(),
+        })
+    }
+
+    /// The number of a heading, e.g. `"Chapter One"`.
+    pub fn label(&self, number: usize) -> String {
+        let number = self.style.format(number);
+        self.template
+            .replace("{NUMBER}", &number.to_uppercase())
+            .replace("{number}", &number)
+    }
+}
+
+/// Numbering of the chapter and part headings, which are unnumbered when undefined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberingLayout {
+    pub chapter: Option<HeadingNumbering>,
+    pub part: Option<HeadingNumbering>,
+    /// Whether the name of a numbered heading is shown below its number.
+    pub titles: bool,
+}
+
+impl Default for NumberingLayout {
+    fn default() -> Self {
+        Self {
+            chapter: None,
+            part: None,
+            titles: true,
+        }
+    }
+}
+
+/// Pagination of the manuscript's pages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaginationLayout {
+    pub widow_control: bool,
+    pub keep_with_next: bool,
+    pub chapter_start: ChapterStart,
+}
+
+impl Default for PaginationLayout {
+    fn default() -> Self {
+        Self {
+            widow_control: true,
+            keep_with_next: true,
+            chapter_start: ChapterStart::default(),
+        }
+    }
+}
+
+/// Layout of the plain text output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    pub wrap_width: Option<usize>,
+    pub scene_break: String,
+}
+
+/// Layout of the LaTeX output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatexLayout {
+    pub class: String,
+    pub class_options: Vec<String>,
+}
+
+/// Layout of the screenplay output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenplayLayout {
+    pub continued: bool,
+}
+
+#[derive(Debug)]
+pub struct ManuscriptBuilderLayout {
+    pub title: String,
+    pub short_title: String,
+    pub pen_name: String,
+    pub language: String,
+    pub subject: Option<String>,
+    pub keywords: Vec<String>,
+    pub author_information: Option<ContactInformation>,
+    pub agent_information: Option<ContactInformation>,
+    pub title_page: TitlePageVariant,
+    pub page: PageLayout,
+    pub font_family: String,
+    pub font_size_point: f32,
+    pub line_spacing: f32,
+    pub first_line_indention_inch: f32,
+    pub scene_break: String,
+    pub paragraph_break: ParagraphBreak,
+    pub block_quote_indention_inch: f32,
+    pub notes: NotePlacement,
+    pub word_count_rounding: WordCountRounding,
+    pub word_count_prefix: String,
+    pub header: Option<RunningHeadLayout>,
+    pub footer: Option<RunningHeadLayout>,
+    pub numbering: NumberingLayout,
+    pub pagination: PaginationLayout,
+    /// The title of the table of contents, which is only included when it's defined.
+    pub table_of_contents: Option<String>,
+    /// The titles of the pages of matter whose lines are centred without a heading.
+    pub untitled_matter: Vec<String>,
+    pub text: TextLayout,
+    pub latex: LatexLayout,
+    pub screenplay: ScreenplayLayout,
+}
+
+impl ManuscriptBuilderLayout {
+    pub const DEFAULT_TITLE: &'static str = "Untitled";
+    pub const DEFAULT_PENNAME: &'static str = "Unknown Author";
+    pub const DEFAULT_LANGUAGE: &'static str = "en";
+    pub const DEFAULT_FONT_FAMILY: &'static str = "Times New Roman";
+    pub const DEFAULT_FONT_SIZE_POINT: f32 = 12.0;
+    pub const DEFAULT_LINE_SPACING: f32 = 2.0;
+    pub const DEFAULT_FIRST_LINE_INDENTION_INCH: f32 = 0.5;
+    pub const DEFAULT_SCENE_BREAK: &'static str = "#";
+    pub const DEFAULT_BLOCK_QUOTE_INDENTION_INCH: f32 = 0.5;
+    pub const DEFAULT_NOTES_TITLE: &'static str = "Notes";
+    pub const DEFAULT_WORD_COUNT_PREFIX: &'static str = "about";
+    pub const DEFAULT_LATEX_CLASS: &'static str = "book";
+    pub const DEFAULT_TABLE_OF_CONTENTS_TITLE: &'static str = "Contents";
+    pub const DEFAULT_UNTITLED_MATTER: [&'static str; 2] = ["Dedication", "Epigraph"];
+    pub const DEFAULT_BYLINE_PREFIX: &'static str = "by";
+
+    /// The last name of the author, which falls back to the pen name when the author's name is
+    /// undefined.
+    pub fn surname(&self) -> String {
+        let name = self
+            .author_information
+            .as_ref()
+            .and_then(|author| author.name.as_ref())
+            .unwrap_or(&self.pen_name);
+
+        name.split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// The word count that's presented on the title page, e.g. `"about 1,200 words"`.
+    pub fn word_count_label(&self, word_count: u32) -> String {
+        let rounded_count = self
+            .word_count_rounding
+            .round(word_count)
+            .to_string()
+            .with_thousands_separator();
+
+        match self.word_count_rounding {
+            WordCountRounding::Exact => format!("{} words", rounded_count),
+            _ if self.word_count_prefix.is_empty() => format!("{} words", rounded_count),
+            _ => format!("{} {} words", self.word_count_prefix, rounded_count),
+        }
+    }
+
+    pub fn title_page_layout(&self, word_count: u32) -> TitlePageLayout {
+        let content_height = self.page.content_height_inch() * 72.0;
+        let third_height = content_height / 3.0;
+        let top_layout = ParagraphLayout {
+            line_spacing_point: self.font_size_point,
+            ..self.paragraph_layout()
+        };
+        let middle_layout = ParagraphLayout {
+            alignment: AlignmentType::Center,
+            ..self.paragraph_layout()
+        };
+        let bottom_layout = ParagraphLayout {
+            alignment: AlignmentType::Right,
+            ..top_layout.clone()
+        };
+
+        let contact_lines = |information: Option<&ContactInformation>| -> Vec<String> {
+            information.map_or(vec![], |information| {
+                information.lines().into_iter().cloned().collect()
+            })
+        };
+        let top_lines = contact_lines(self.author_information.as_ref());
+        let byline = format!("{} {}", Self::DEFAULT_BYLINE_PREFIX, self.pen_name);
+        let word_count = self.word_count_label(word_count);
+        let (middle_lines, top_right_line, bottom_lines) = match self.title_page {
+            TitlePageVariant::Standard => (
+                vec![self.title.clone(), self.pen_name.clone(), word_count],
+                None,
+                contact_lines(self.agent_information.as_ref()),
+            ),
+            TitlePageVariant::ShortStory => {
+                (vec![self.title.clone(), byline], Some(word_count), vec![])
+            }
+            TitlePageVariant::Novel => (
+                vec![self.title.clone(), byline],
+                Some(word_count),
+                contact_lines(self.agent_information.as_ref()),
+            ),
+            TitlePageVariant::Anonymous => (vec![self.title.clone(), word_count], None, vec![]),
+        };
+
+        let top_height =
+            top_lines.len().max(top_right_line.iter().len()) as f32 * top_layout.line_spacing_point;
+        let middle_height = middle_lines.len() as f32 * middle_layout.line_spacing_point;
+        let middle_top = match self.title_page {
+            TitlePageVariant::ShortStory => third_height,
+            _ => third_height + (third_height - middle_height).max(0.0) / 2.0,
+        };
+        let bottom_top =
+            content_height - bottom_lines.len() as f32 * bottom_layout.line_spacing_point;
+
+        TitlePageLayout {
+            top_lines,
+            top_right_line,
+            middle_lines,
+            bottom_lines,
+            top_layout,
+            middle_layout,
+            bottom_layout,
+            middle_space_before_point: (middle_top - top_height).max(0.0),
+            bottom_space_before_point: (bottom_top - middle_top - middle_height).max(0.0),
+        }
+    }
+
+    pub fn line_spacing_point(&self) -> f32 {
+        self.font_size_point * self.line_spacing
+    }
+
+    pub fn paragraph_layout(&self) -> ParagraphLayout {
+        ParagraphLayout {
+            font_family: self.font_family.clone(),
+            font_size_point: self.font_size_point,
+            line_spacing_point: self.line_spacing_point(),
+            after_line_spacing_point: 0.0,
+            first_line_indention_inch: 0.0,
+            left_indention_inch: 0.0,
+            right_indention_inch: 0.0,
+            alignment: AlignmentType::Left,
+        }
+    }
+
+    /// Layout of a paragraph of a block quote, which is indented from both sides.
+    pub fn block_quote_layout(&self) -> ParagraphLayout {
+        ParagraphLayout {
+            left_indention_inch: self.block_quote_indention_inch,
+            right_indention_inch: self.block_quote_indention_inch,
+            ..self.paragraph_layout()
+        }
+    }
+}
+
+impl Default for ManuscriptBuilderLayout {
+    fn default() -> Self {
+        Self {
+            title: Self::DEFAULT_TITLE.into(),
+            short_title: Self::DEFAULT_TITLE.into(),
+            pen_name: Self::DEFAULT_PENNAME.into(),
+            language: Self::DEFAULT_LANGUAGE.into(),
+            subject: None,
+            keywords: vec![],
+            author_information: None,
+            agent_information: None,
+            title_page: TitlePageVariant::default(),
+            page: PageLayout::default(),
+            font_family: Self::DEFAULT_FONT_FAMILY.into(),
+            font_size_point: Self::DEFAULT_FONT_SIZE_POINT,
+            line_spacing: Self::DEFAULT_LINE_SPACING,
+            first_line_indention_inch: Self::DEFAULT_FIRST_LINE_INDENTION_INCH,
+            scene_break: Self::DEFAULT_SCENE_BREAK.into(),
+            paragraph_break: ParagraphBreak::default(),
+            block_quote_indention_inch: Self::DEFAULT_BLOCK_QUOTE_INDENTION_INCH,
+            notes: NotePlacement::default(),
+            word_count_rounding: WordCountRounding::Exact,
+            word_count_prefix: Self::DEFAULT_WORD_COUNT_PREFIX.into(),
+            header: RunningHeadLayout::from_config(
+                None,
+                Some(RunningHeadLayout::DEFAULT_HEADER_TEMPLATE),
+            ),
+            footer: None,
+            numbering: NumberingLayout::default(),
+            pagination: PaginationLayout::default(),
+            table_of_contents: None,
+            untitled_matter: Self::DEFAULT_UNTITLED_MATTER.map(String::from).to_vec(),
+            text: TextLayout {
+                wrap_width: None,
+                scene_break: Self::DEFAULT_SCENE_BREAK.into(),
+            },
+            latex: LatexLayout {
+                class: Self::DEFAULT_LATEX_CLASS.into(),
+                class_options: vec![],
+            },
+            screenplay: ScreenplayLayout { continued: false },
+        }
+    }
+}
+
+impl From<&Config> for ManuscriptBuilderLayout {
+    fn from(value: &Config) -> Self {
+        let title = match value.story.as_ref() {
+            Some(story_config) => story_config
+                .title
+                .as_ref()
+                .clone_on_some(Self::DEFAULT_TITLE.to_string()),
+            None => Self::DEFAULT_TITLE.to_string(),
+        };
+        let title_page = value
+            .title_page
+            .as_ref()
+            .and_then(|title_page| title_page.variant)
+            .unwrap_or_default();
+        // An anonymous manuscript has no trace of its author in any of its outputs.
+        let is_anonymous = title_page == TitlePageVariant::Anonymous;
+        let pen_name = match value.story.as_ref() {
+            _ if is_anonymous => String::new(),
+            Some(story_config) => story_config
+                .pen_name
+                .as_ref()
+                .clone_on_some(Self::DEFAULT_PENNAME.to_string()),
+            None => Self::DEFAULT_PENNAME.to_string(),
+        };
+        let short_title = value
+            .story
+            .as_ref()
+            .and_then(|story_config| story_config.short_title.clone())
+            .unwrap_or(title.clone());
+        let language = value
+            .story
+            .as_ref()
+            .and_then(|story_config| story_config.language.clone())
+            .unwrap_or(Self::DEFAULT_LANGUAGE.to_string());
+        let default = Self::default();
+        let layout = value.layout.as_ref();
+        let word_count = value.word_count.as_ref();
+        let scene_break = layout
+            .and_then(|layout| layout.scene_break.clone())
+            .unwrap_or(default.scene_break);
+
+        Self {
+            title,
+            short_title,
+            pen_name,
+            language,
+            subject: value
+                .story
+                .as_ref()
+                .and_then(|story_config| story_config.subject.clone()),
+            keywords: value
+                .story
+                .as_ref()
+                .and_then(|story_config| story_config.keywords.clone())
+                .unwrap_or_default(),
+            author_information: value.author.clone().filter(|_| !is_anonymous),
+            agent_information: value.agent.clone().filter(|_| !is_anonymous),
+            title_page,
+            page: layout.map_or(default.page, PageLayout::from),
+            font_family: layout
+                .and_then(|layout| layout.font_family.clone())
+                .unwrap_or(default.font_family),
+            font_size_point: layout
+                .and_then(|layout| layout.font_size)
+                .unwrap_or(default.font_size_point),
+            line_spacing: layout
+                .and_then(|layout| layout.line_spacing)
+                .unwrap_or(default.line_spacing),
+            first_line_indention_inch: layout
+                .and_then(|layout| layout.first_line_indent)
+                .unwrap_or(default.first_line_indention_inch),
+            scene_break: scene_break.clone(),
+            paragraph_break: layout
+                .and_then(|layout| layout.paragraph_break)
+                .unwrap_or(default.paragraph_break),
+            block_quote_indention_inch: layout
+                .and_then(|layout| layout.block_quote_indent)
+                .unwrap_or(default.block_quote_indention_inch),
+            notes: layout
+                .and_then(|layout| layout.notes)
+                .unwrap_or(default.notes),
+            word_count_rounding: word_count
+                .and_then(|word_count| word_count.rounding)
+                .unwrap_or(default.word_count_rounding),
+            word_count_prefix: word_count
+                .and_then(|word_count| word_count.prefix.clone())
+                .unwrap_or(default.word_count_prefix),
+            header: RunningHeadLayout::from_config(
+                value.header.as_ref(),
+                Some(match is_anonymous {
+                    true => RunningHeadLayout::DEFAULT_ANONYMOUS_HEADER_TEMPLATE,
+                    false => RunningHeadLayout::DEFAULT_HEADER_TEMPLATE,
+                }),
+            ),
+            footer: RunningHeadLayout::from_config(value.footer.as_ref(), None),
+            numbering: match value.numbering.as_ref() {
+                Some(numbering) => NumberingLayout {
+                    chapter: HeadingNumbering::from_config(
+                        numbering.chapter.as_ref(),
+                        numbering.chapter_style,
+                    ),
+                    part: HeadingNumbering::from_config(
+                        numbering.part.as_ref(),
+                        numbering.part_style,
+                    ),
+                    titles: numbering.titles.unwrap_or(default.numbering.titles),
+                },
+                None => default.numbering,
+            },
+            pagination: match value.pagination.as_ref() {
+                Some(pagination) => PaginationLayout {
+                    widow_control: pagination
+                        .widow_control
+                        .unwrap_or(default.pagination.widow_control),
+                    keep_with_next: pagination
+                        .keep_with_next
+                        .unwrap_or(default.pagination.keep_with_next),
+                    chapter_start: pagination
+                        .chapter_start
+                        .unwrap_or(default.pagination.chapter_start),
+                },
+                None => default.pagination,
+            },
+            table_of_contents: value
+                .table_of_contents
+                .as_ref()
+                .filter(|table_of_contents| table_of_contents.enabled.unwrap_or(true))
+                .map(|table_of_contents| {
+                    table_of_contents
+                        .title
+                        .clone()
+                        .unwrap_or(Self::DEFAULT_TABLE_OF_CONTENTS_TITLE.into())
+                }),
+            untitled_matter: value
+                .project
+                .as_ref()
+                .and_then(|project| project.untitled_matter.clone())
+                .unwrap_or(default.untitled_matter),
+            text: TextLayout {
+                wrap_width: value
+                    .text
+                    .as_ref()
+                    .and_then(|text| text.wrap)
+                    .filter(|wrap_width| *wrap_width > 0),
+                scene_break: value
+                    .text
+                    .as_ref()
+                    .and_then(|text| text.scene_break.clone())
+                    .unwrap_or(scene_break),
+            },
+            latex: LatexLayout {
+                class: value
+                    .latex
+                    .as_ref()
+                    .and_then(|latex| latex.class.clone())
+                    .unwrap_or(default.latex.class),
+                class_options: value
+                    .latex
+                    .as_ref()
+                    .and_then(|latex| latex.class_options.clone())
+                    .unwrap_or(default.latex.class_options),
+            },
+            screenplay: ScreenplayLayout {
+                continued: value
+                    .screenplay
+                    .as_ref()
+                    .and_then(|screenplay| screenplay.continued)
+                    .unwrap_or(default.screenplay.continued),
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ManuscriptBuilder {
+    pub layout: ManuscriptBuilderLayout,
+    /// The content of the images that the scenes place, keyed by their path in the project.
+    pub images: HashMap<String, Vec<u8>>,
+}
+
+impl ManuscriptBuilder {
+    pub fn new(layout: impl Into<ManuscriptBuilderLayout>) -> Self {
+        Self {
+            layout: layout.into(),
+            images: HashMap::new(),
+        }
+    }
+
+    fn paragraph(text: &str, layout: ParagraphLayout) -> Paragraph {
+        Self::spans_paragraph(&[Span::Text(text.to_string())], layout)
+    }
+
+    fn spans_paragraph(spans: &[Span], layout: ParagraphLayout) -> Paragraph {
+        let mut paragraph = Paragraph::new()
+            .align(layout.alignment)
+            .fonts(
+                RunFonts::new()
+                    .ascii(&layout.font_family)
+                    .hi_ansi(&layout.font_family),
+            )
+            .size(HalfPoint::from_point(layout.font_size_point).into());
+
+        for span in spans {
+            let run = Run::new()
+                .add_text(span.text())
+                .fonts(
+                    RunFonts::new()
+                        .ascii(&layout.font_family)
+                        .hi_ansi(&layout.font_family),
+                )
+                .size(HalfPoint::from_point(layout.font_size_point).into());
+
+            paragraph = match span {
+                Span::Emphasis(_) => paragraph.add_run(run.italic()),
+                Span::Text(_) => paragraph.add_run(run),
+                // The paragraphs of the title page have no notes.
+                Span::Note(_) => paragraph,
+            };
+        }
+
+        paragraph
+            .line_spacing(
+                LineSpacing::new()
+                    .line_rule(LineSpacingType::Auto)
+                    .line(Twip::from_point(layout.line_spacing_point).into())
+                    .after(Twip::from_point(layout.after_line_spacing_point).into()),
+            )
+            .indent(
+                None,
+                Some(SpecialIndentType::FirstLine(
+                    Twip::from_inch(layout.first_line_indention_inch).into(),
+                )),
+                None,
+                None,
+            )
+    }
+
+    /// Builds a paragraph that's only formatted through its named style, apart from the
+    /// emphasis of its spans and the references to its notes.
+    fn styled_paragraph(&self, text: &str, style_id: &str, notes: &mut Notes) -> Paragraph {
+        Span::parse(text)
+            .iter()
+            .fold(Paragraph::new().style(style_id), |paragraph, span| {
+                paragraph.add_run(match span {
+                    Span::Emphasis(text) => Run::new().add_text(text).italic(),
+                    Span::Text(text) => Run::new().add_text(text),
+                    Span::Note(note) => self.docx_note_run(notes.push(note)),
+                })
+            })
+    }
+
+    /// Builds a paragraph of literal text that's only formatted through its named style.
+    fn styled_text_paragraph(text: &str, style_id: &str) -> Paragraph {
+        Paragraph::new()
+            .style(style_id)
+            .add_run(Run::new().add_text(text))
+    }
+
+    fn running_head_paragraph(&self, running_head: &RunningHeadLayout) -> Paragraph {
+        let fonts = RunFonts::new()
+            .ascii(&self.layout.font_family)
+            .hi_ansi(&self.layout.font_family);
+        let size: usize = HalfPoint::from_point(self.layout.font_size_point).into();
+        let field_runs = |instr_text: InstrText| {
+            vec![
+                Run::new().add_field_char(FieldCharType::Begin, false),
+                Run::new().add_instr_text(instr_text),
+                Run::new().add_field_char(FieldCharType::Separate, false),
+                Run::new().add_text("1"),
+                Run::new().add_field_char(FieldCharType::End, false),
+            ]
+        };
+
+        running_head
+            .segments(&self.layout)
+            .into_iter()
+            .flat_map(|segment| match segment {
+                RunningHeadSegment::Text(text) => vec![Run::new().add_text(text)],
+                RunningHeadSegment::PageNumber => field_runs(InstrText::PAGE(InstrPAGE::new())),
+                RunningHeadSegment::PageCount => {
+                    field_runs(InstrText::Unsupported("NUMPAGES".into()))
+                }
+            })
+            .fold(
+                Paragraph::new()
+                    .align(running_head.alignment)
+                    .fonts(fonts.clone())
+                    .size(size),
+                |paragraph, run| paragraph.add_run(run.fonts(fonts.clone()).size(size)),
+            )
+    }
+
+    fn build_running_heads(&self, mut doc: Docx) -> Docx {
+        if let Some(header) = &self.layout.header {
+            let paragraph = self.running_head_paragraph(header);
+            doc = doc.header(Header::new().add_paragraph(paragraph.clone()));
+            doc = doc.first_header(match header.first_page {
+                true => Header::new().add_paragraph(paragraph),
+                false => Header::new(),
+            });
+        }
+
+        if let Some(footer) = &self.layout.footer {
+            let paragraph = self.running_head_paragraph(footer);
+            doc = doc.footer(Footer::new().add_paragraph(paragraph.clone()));
+            doc = doc.first_footer(match footer.first_page {
+                true => Footer::new().add_paragraph(paragraph),
+                false => Footer::new(),
+            });
+        }
+
+        doc
+    }
+
+    fn build_document(&self) -> Docx {
+        let page = &self.layout.page;
+
+        Docx::new()
+            .page_size(
+                Twip::from_inch(page.width_inch).into(),
+                Twip::from_inch(page.height_inch).into(),
+            )
+            .page_margin(
+                PageMargin::new()
+                    .top(Twip::from_inch(page.margin_top_inch).into())
+                    .bottom(Twip::from_inch(page.margin_bottom_inch).into())
+                    .left(Twip::from_inch(page.margin_left_inch).into())
+                    .right(Twip::from_inch(page.margin_right_inch).into()),
+            )
+    }
+
+    /// Builds the title page as a table whose rows are the thirds of the page. A short story
+    /// only has the top row, and its title and byline are written right below it.
+    fn build_title_page(&self, doc: Docx, word_count: u32) -> Docx {
+        let title_page = self.layout.title_page_layout(word_count);
+        let row_height: f32 = Twip::from_inch(self.layout.page.content_height_inch() / 3.0).into();
+        let paragraphs = |lines: &[String], layout: &ParagraphLayout| -> Vec<Paragraph> {
+            lines
+                .iter()
+                .map(|line| Self::paragraph(line, layout.clone()))
+                .collect()
+        };
+        let row = |paragraphs: Vec<Paragraph>, alignment: VAlignType| {
+            let cell = TableCell::new()
+                .clear_all_border()
+                .vertical_align(alignment);
+            TableRow::new(vec![paragraphs
+                .into_iter()
+                .fold(cell, |cell, paragraph| cell.add_paragraph(paragraph))])
+            .row_height(row_height)
+        };
+
+        let content_width: usize = Twip::from_inch(self.layout.page.content_width_inch()).into();
+        let top_paragraphs = title_page
+            .top_rows()
+            .into_iter()
+            .map(|(line, right_line)| {
+                let paragraph = Self::paragraph(line, title_page.top_layout.clone());
+                match right_line {
+                    Some(right_line) => paragraph
+                        .add_tab(Tab::new().val(TabValueType::Right).pos(content_width))
+                        .add_run(
+                            Run::new()
+                                .add_tab()
+                                .add_text(right_line)
+                                .fonts(
+                                    RunFonts::new()
+                                        .ascii(&self.layout.font_family)
+                                        .hi_ansi(&self.layout.font_family),
+                                )
+                                .size(HalfPoint::from_point(self.layout.font_size_point).into()),
+                        ),
+                    None => paragraph,
+                }
+            })
+            .collect();
+
+        let mut middle_paragraphs = paragraphs(
+            title_page.middle_lines.get(1..).unwrap_or_default(),
+            &title_page.middle_layout,
+        );
+        middle_paragraphs.insert(
+            0,
+            Self::styled_text_paragraph(&self.layout.title, TITLE_STYLE),
+        );
+
+        let table_rows = match self.layout.title_page {
+            TitlePageVariant::ShortStory => vec![row(top_paragraphs, VAlignType::Top)],
+            _ => vec![
+                row(top_paragraphs, VAlignType::Top),
+                row(std::mem::take(&mut middle_paragraphs), VAlignType::Center),
+                row(
+                    paragraphs(&title_page.bottom_lines, &title_page.bottom_layout),
+                    VAlignType::Bottom,
+                ),
+            ],
+        };
+
+        middle_paragraphs.into_iter().fold(
+            doc.add_table(Table::new(table_rows).width(
+                Twip::from_inch(self.layout.page.content_width_inch()).into(),
+                WidthType::Auto,
+            )),
+            |doc, paragraph| doc.add_paragraph(paragraph),
+        )
+    }
+
+    /// The blocks of the document that follow the title page. The untitled heading that opens
+    /// a short story is left out, so the story follows its title right on the first page.
+    pub(crate) fn paged_blocks<'a>(&self, document: &'a ManuscriptDocument) -> &'a [Block] {
+        match (self.layout.title_page, document.blocks.first()) {
+            (TitlePageVariant::ShortStory, Some(block @ Block::ChapterHeading(_)))
+                if block.heading_lines().is_empty() =>
+            {
+                &document.blocks[1..]
+            }
+            _ => &document.blocks,
+        }
+    }
+
+    /// Starts a new page whose content begins a third of the way down.
+    fn build_page_opening(&self, doc: Docx) -> Docx {
+        doc.add_paragraph(self.docx_page_break_paragraph())
+            .add_table(
+                Table::new(vec![TableRow::new(vec![TableCell::new()]).row_height(
+                    Twip::from_inch(self.layout.page.content_height_inch() / 3.0).into(),
+                )])
+                .clear_all_border(),
+            )
+    }
+
+    fn build_block(
+        &self,
+        doc: Docx,
+        document: &ManuscriptDocument,
+        block: &Block,
+        notes: &mut Notes,
+    ) -> Result<Docx, BuilderError> {
+        Ok(match block {
+            Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {
+                let doc = self.build_page_opening(doc);
+                let lines = block.heading_lines();
+                if lines.is_empty() {
+                    return Ok(doc);
+                }
+
+                let style_id = match block {
+                    Block::ChapterHeading(_) if document.has_parts() => HEADING_2_STYLE,
+                    _ => HEADING_1_STYLE,
+                };
+                let paragraph = lines.iter().enumerate().fold(
+                    Paragraph::new().style(style_id),
+                    |paragraph, (index, line)| {
+                        let run = match index {
+                            0 => Run::new(),
+                            _ => Run::new().add_break(docx_rs::BreakType::TextWrapping),
+                        };
+                        paragraph.add_run(run.add_text(*line))
+                    },
+                );
+                doc.add_paragraph(paragraph)
+            }
+            Block::Paragraph(text) => {
+                doc.add_paragraph(self.styled_paragraph(text, NORMAL_STYLE, notes))
+            }
+            Block::FirstParagraph(text) => {
+                doc.add_paragraph(self.styled_paragraph(text, FIRST_PARAGRAPH_STYLE, notes))
+            }
+            Block::CenteredParagraph(text) => {
+                doc.add_paragraph(self.styled_paragraph(text, CENTERED_STYLE, notes))
+            }
+            Block::BlockQuote(text) => {
+                doc.add_paragraph(self.styled_paragraph(text, BLOCK_QUOTE_STYLE, notes))
+            }
+            Block::Image(image) => doc.add_paragraph(self.docx_image_paragraph(image)?),
+            Block::SceneBreak => doc.add_paragraph(Self::styled_text_paragraph(
+                &self.layout.scene_break,
+                SCENE_BREAK_STYLE,
+            )),
+        })
+    }
+
+    /// Builds the word document, whose paragraphs are styled by the named styles of the
+    /// `reference` document when it's given. Refer to [`styles`] for the names of the styles.
+    pub fn build_docx(
+        &self,
+        story: &Story,
+        reference: Option<&[u8]>,
+    ) -> Result<DocxPackage, BuilderError> {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let word_count = WordCount::count_document(&document);
+
+        let mut doc = self.build_document();
+        doc = self.build_running_heads(doc);
+        doc = self.build_title_page(doc, word_count);
+
+        if let Some(title) = &self.layout.table_of_contents {
+            doc = doc.add_table_of_contents(
+                TableOfContents::new()
+                    .heading_styles_range(1, 2)
+                    .hyperlink()
+                    .alias(title)
+                    .add_before_paragraph(
+                        Self::styled_text_paragraph(title, TOC_HEADING_STYLE)
+                            .page_break_before(true),
+                    ),
+            );
+        }
+
+        let mut notes = Notes::default();
+        for block in self.paged_blocks(&document) {
+            doc = self.build_block(doc, &document, block, &mut notes)?;
+        }
+
+        doc = self.build_docx_custom_properties(doc);
+        let mut xml_docx = doc.build();
+        xml_docx.styles = self.build_docx_styles(reference)?;
+        xml_docx.doc_props.core = self.build_docx_core_properties(&SystemTime::now().to_iso8601());
+        xml_docx.doc_props.app = self.build_docx_app_properties(word_count);
+
+        let mut package = DocxPackage::new(xml_docx);
+        self.add_docx_section_breaks(&mut package);
+        self.add_docx_notes(&mut package, notes.take());
+
+        Ok(package)
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::*;
+
+    #[test]
+    fn builds_docx() {
+        let mock_story = {
+            let mut story = Story::new("Root");
+
+            let mut chapter_1 = Story::new("Chapter 1");
+            chapter_1.push_content("I am Scene #1.");
+            chapter_1.push_content("I am Scene #2.");
+
+            story.push_part(chapter_1);
+
+            story
+        };
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let result = builder.build_docx(&mock_story, None);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn builds_docx_table_of_contents() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am Scene #1.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let document = builder.build_docx(&story, None).unwrap().docx.document;
+        assert!(!String::from_utf8_lossy(&document).contains("TOCHeading"));
+
+        let layout = ManuscriptBuilderLayout {
+            table_of_contents: Some("Contents".into()),
+            ..Default::default()
+        };
+        let builder = ManuscriptBuilder::new(layout);
+        let document = builder.build_docx(&story, None).unwrap().docx.document;
+        let document = String::from_utf8_lossy(&document);
+        assert!(document.contains(r#"TOC \o &quot;1-2&quot;"#));
+        assert!(document.contains(r#"<w:pStyle w:val="TOCHeading" />"#));
+    }
+
+    #[test]
+    fn builds_docx_section_breaks() {
+        let mut story = Story::new("Root");
+        for name in ["Chapter 1", "Chapter 2"] {
+            let mut chapter = Story::new(name);
+            chapter.push_content("I am a scene.");
+            story.push_part(chapter);
+        }
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let document = builder.build_docx(&story, None).unwrap().docx.document;
+        let document = String::from_utf8_lossy(&document);
+        assert_eq!(document.matches("<w:sectPr>").count(), 1);
+        assert!(document.contains(r#"<w:br w:type="page" />"#));
+
+        let layout = ManuscriptBuilderLayout {
+            pagination: PaginationLayout {
+                chapter_start: ChapterStart::Recto,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let builder = ManuscriptBuilder::new(layout);
+        let document = builder.build_docx(&story, None).unwrap().docx.document;
+        let document = String::from_utf8_lossy(&document);
+        assert_eq!(document.matches("<w:sectPr>").count(), 3);
+        assert_eq!(document.matches(r#"<w:type w:val="oddPage" />"#).count(), 2);
+        assert_eq!(document.matches("<w:titlePg />").count(), 1);
+        assert!(!document.contains(r#"<w:br w:type="page" />"#));
+    }
+
+    #[test]
+    fn builds_docx_images() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("![A map](map.png){width=4 align=right}\nI am Scene #1.");
+        story.push_part(chapter_1);
+
+        let mut builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        assert!(matches!(
+            builder.build_docx(&story, None),
+            Err(BuilderError::MissingImage(path)) if path == "map.png"
+        ));
+
+        let mut map = std::io::Cursor::new(vec![]);
+        image::DynamicImage::new_rgb8(192, 96)
+            .write_to(&mut map, image::ImageOutputFormat::Png)
+            .unwrap();
+        builder.images.insert("map.png".into(), map.into_inner());
+
+        let package = builder.build_docx(&story, None).unwrap();
+        let document = String::from_utf8_lossy(&package.docx.document);
+        assert!(document.contains(r#"<w:jc w:val="right" />"#));
+        assert!(document.contains(r#"<wp:extent cx="3657600" cy="1828800" />"#));
+        assert_eq!(package.docx.media.len(), 1);
+    }
+
+    #[test]
+    fn builds_docx_notes() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I have *never*^[Not *once*.] been to the sea.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let package = builder.build_docx(&story, None).unwrap();
+        let document = String::from_utf8_lossy(&package.docx.document);
+        assert!(document.contains(
+            r#"<w:rStyle w:val="FootnoteReference" /></w:rPr><w:footnoteReference w:id="2" />"#
+        ));
+        assert!(!document.contains('\u{E000}'));
+        assert!(String::from_utf8_lossy(&package.docx.content_type).contains("/word/footnotes.xml"));
+
+        let mut packed = std::io::Cursor::new(vec![]);
+        package.pack(&mut packed).unwrap();
+        let mut footnotes = String::new();
+        std::io::Read::read_to_string(
+            &mut zip::ZipArchive::new(packed)
+                .unwrap()
+                .by_name("word/footnotes.xml")
+                .unwrap(),
+            &mut footnotes,
+        )
+        .unwrap();
+        assert!(footnotes.contains(r#"<w:footnote w:type="separator" w:id="0">"#));
+        assert!(footnotes.contains(r#"<w:footnote w:id="2"><w:p><w:pPr><w:pStyle w:val="FootnoteText"/></w:pPr><w:r><w:rPr><w:rStyle w:val="FootnoteReference"/></w:rPr><w:footnoteRef/></w:r><w:r><w:t xml:space="preserve"> </w:t></w:r><w:r><w:t xml:space="preserve">Not </w:t></w:r><w:r><w:rPr><w:i/></w:rPr><w:t xml:space="preserve">once</w:t></w:r>"#));
+    }
+
+    #[test]
+    fn builds_layout_from_config() {
+        let config = Config::parse(
+            r#"[layout]
+page_size = "a4"
+margins = { left = 1.5 }
+font_family = "Courier New"
+line_spacing = 1.5
+"#,
+        )
+        .unwrap();
+
+        let layout = ManuscriptBuilderLayout::from(&config);
+        assert_eq!(layout.page.width_inch, 8.27);
+        assert_eq!(layout.page.margin_left_inch, 1.5);
+        assert_eq!(layout.page.margin_right_inch, 1.0);
+        assert_eq!(layout.font_family, "Courier New");
+        assert_eq!(layout.line_spacing_point(), 18.0);
+        assert_eq!(layout.paragraph_layout().font_family, "Courier New");
+    }
+
+    #[test]
+    fn labels_word_count() {
+        let config = Config::parse(
+            r#"[word_count]
+rounding = "hundred"
+"#,
+        )
+        .unwrap();
+
+        let layout = ManuscriptBuilderLayout::from(&config);
+        assert_eq!(layout.word_count_label(1_237), "about 1,200 words");
+
+        let layout = ManuscriptBuilderLayout::default();
+        assert_eq!(layout.word_count_label(1_237), "1,237 words");
+    }
+
+    #[test]
+    fn expands_running_head() {
+        let config = Config::parse(
+            r#"[story]
+title = "Untitled Story"
+short_title = "UNTITLED"
+
+[author]
+name = "Brutus Ellis"
+
+[footer]
+text = "{page} of {pages}"
+"#,
+        )
+        .unwrap();
+
+        let layout = ManuscriptBuilderLayout::from(&config);
+        let header = layout.header.as_ref().unwrap();
+        assert_eq!(
+            header.segments(&layout),
+            vec![
+                RunningHeadSegment::Text("Ellis / UNTITLED / ".into()),
+                RunningHeadSegment::PageNumber,
+            ]
+        );
+
+        let footer = layout.footer.as_ref().unwrap();
+        assert_eq!(
+            footer.segments(&layout),
+            vec![
+                RunningHeadSegment::PageNumber,
+                RunningHeadSegment::Text(" of ".into()),
+                RunningHeadSegment::PageCount,
+            ]
+        );
+    }
+
+    #[test]
+    fn lays_out_title_page_variants() {
+        let layout = ManuscriptBuilderLayout {
+            title: "Mock Story".into(),
+            pen_name: "B. Ellis".into(),
+            title_page: TitlePageVariant::ShortStory,
+            ..Default::default()
+        };
+
+        let title_page = layout.title_page_layout(1_237);
+        assert_eq!(title_page.middle_lines, vec!["Mock Story", "by B. Ellis"]);
+        assert_eq!(title_page.top_right_line.as_deref(), Some("1,237 words"));
+        assert_eq!(title_page.top_rows(), vec![("", Some("1,237 words"))]);
+        assert_eq!(
+            title_page.middle_space_before_point,
+            layout.page.content_height_inch() * 72.0 / 3.0 - layout.font_size_point
+        );
+    }
+
+    #[test]
+    fn anonymizes_title_page() {
+        let config = Config::parse(
+            r#"[story]
+title = "Mock Story"
+pen_name = "B. Ellis"
+
+[author]
+name = "Brutus Ellis"
+email_address = "brutus@example.com"
+
+[title_page]
+variant = "anonymous"
+"#,
+        )
+        .unwrap();
+
+        let layout = ManuscriptBuilderLayout::from(&config);
+        assert!(layout.author_information.is_none());
+        assert_eq!(layout.surname(), "");
+        assert_eq!(
+            layout.header.as_ref().unwrap().segments(&layout)[0],
+            RunningHeadSegment::Text("Mock Story / ".into())
+        );
+
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am Scene #1.");
+        story.push_part(chapter_1);
+
+        let package = ManuscriptBuilder::new(layout)
+            .build_docx(&story, None)
+            .unwrap();
+        let document = String::from_utf8_lossy(&package.docx.document);
+        assert!(document.contains("Mock Story"));
+        assert!(!document.contains("Ellis"));
+        assert!(!document.contains("brutus@example.com"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Footnotes and endnotes of the word document.
+//!
+//! `docx_rs` doesn't write notes, so a reference is written as a run of a marker text that's
+//! replaced with the actual reference once the document is built, and the notes are written to
+//! their own part of the package.
+
+use docx_rs::Run;
+
+use super::{
+    document::Span,
+    package::DocxPackage,
+    styles::{
+        ENDNOTE_REFERENCE_STYLE, ENDNOTE_TEXT_STYLE, FOOTNOTE_REFERENCE_STYLE, FOOTNOTE_TEXT_STYLE,
+    },
+    ManuscriptBuilder,
+};
+use crate::{config::NotePlacement, extensions::EscapeXml};
+
+/// Private use character that encloses the number of a note in its marker text.
+const DOCX_NOTE_MARKER: char = '\u{E000}';
+
+/// Ids of the separator notes, which precede the ids of the notes of the story.
+const DOCX_SEPARATOR_NOTE_IDS: usize = 2;
+
+/// Names that differ between footnotes and endnotes.
+struct DocxNoteKind {
+    path: &'static str,
+    element: &'static str,
+    content_type: &'static str,
+    relationship_type: &'static str,
+    text_style: &'static str,
+    reference_style: &'static str,
+}
+
+impl DocxNoteKind {
+    fn from_placement(placement: NotePlacement) -> Self {
+        match placement {
+            NotePlacement::Footnotes => Self {
+                path: "word/footnotes.xml",
+                element: "footnote",
+                content_type:
+                    "application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml",
+                relationship_type:
+                    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/footnotes",
+                text_style: FOOTNOTE_TEXT_STYLE,
+                reference_style: FOOTNOTE_REFERENCE_STYLE,
+            },
+            NotePlacement::Endnotes => Self {
+                path: "word/endnotes.xml",
+                element: "endnote",
+                content_type:
+                    "application/vnd.openxmlformats-officedocument.wordprocessingml.endnotes+xml",
+                relationship_type:
+                    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/endnotes",
+                text_style: ENDNOTE_TEXT_STYLE,
+                reference_style: ENDNOTE_REFERENCE_STYLE,
+            },
+        }
+    }
+
+    fn separator_xml(&self, id: usize, separator_type: &str, separator: &str) -> String {
+        format!(
+            r#"<w:{0} w:type="{1}" w:id="{2}"><w:p><w:pPr><w:spacing w:after="0" w:line="240" w:lineRule="auto"/></w:pPr><w:r><w:{3}/></w:r></w:p></w:{0}>"#,
+            self.element, separator_type, id, separator
+        )
+    }
+
+    fn note_xml(&self, number: usize, text: &str) -> String {
+        let runs: String = Span::parse(text)
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) => format!(
+                    r#"<w:r><w:t xml:space="preserve">{}</w:t></w:r>"#,
+                    text.escape_xml()
+                ),
+                Span::Emphasis(text) => format!(
+                    r#"<w:r><w:rPr><w:i/></w:rPr><w:t xml:space="preserve">{}</w:t></w:r>"#,
+                    text.escape_xml()
+                ),
+                Span::Note(_) => String::new(),
+            })
+            .collect();
+
+        format!(
+            r#"<w:{0} w:id="{1}"><w:p><w:pPr><w:pStyle w:val="{2}"/></w:pPr><w:r><w:rPr><w:rStyle w:val="{3}"/></w:rPr><w:{0}Ref/></w:r><w:r><w:t xml:space="preserve"> </w:t></w:r>{4}</w:p></w:{0}>"#,
+            self.element,
+            number + DOCX_SEPARATOR_NOTE_IDS - 1,
+            self.text_style,
+            self.reference_style,
+            runs
+        )
+    }
+}
+
+impl ManuscriptBuilder {
+    /// The run of a note's reference, whose marker text is replaced by [`Self::add_docx_notes`].
+    pub(crate) fn docx_note_run(&self, number: usize) -> Run {
+        Run::new()
+            .style(DocxNoteKind::from_placement(self.layout.notes).reference_style)
+            .add_text(format!("{0}{1}{0}", DOCX_NOTE_MARKER, number))
+    }
+
+    /// Replaces the marker texts of the notes' references in the main document, and adds the
+    /// part that holds the notes.
+    pub(crate) fn add_docx_notes(&self, package: &mut DocxPackage, notes: Vec<(usize, String)>) {
+        if notes.is_empty() {
+            return;
+        }
+
+        let kind = DocxNoteKind::from_placement(self.layout.notes);
+        let mut document = String::from_utf8_lossy(&package.docx.document).to_string();
+        let mut definitions = kind.separator_xml(0, "separator", "separator")
+            + &kind.separator_xml(1, "continuationSeparator", "continuationSeparator");
+
+        for (number, text) in notes {
+            document = document.replace(
+                &format!(
+                    r#"<w:t xml:space="preserve">{0}{1}{0}</w:t>"#,
+                    DOCX_NOTE_MARKER, number
+                ),
+                &format!(
+                    r#"<w:{}Reference w:id="{}" />"#,
+                    kind.element,
+                    number + DOCX_SEPARATOR_NOTE_IDS - 1
+                ),
+            );
+            definitions.push_str(&kind.note_xml(number, &text));
+        }
+
+        package.docx.document = document.into_bytes();
+        package.add_document_part(
+            kind.path,
+            kind.content_type,
+            kind.relationship_type,
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:{0}s xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">{1}</w:{0}s>"#,
+                kind.element, definitions
+            )
+            .into_bytes(),
+        );
+    }
+}
@@ -0,0 +1,590 @@
+//! OpenDocument Text (ODT) output of the manuscript.
+//!
+//! The document mirrors the word document: a title page with the contact information and the
+//! word count, chapters that start on a new page a third of the way down, and running heads with
+//! page number fields. The title page is the first page of the `First Page` master page, which
+//! only carries the running heads that are shown on the first page. Notes are written as
+//! OpenDocument footnotes or endnotes, which readers number and place themselves.
+
+use std::io::{Cursor, Write};
+
+use docx_rs::AlignmentType;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use super::{
+    document::{Block, ManuscriptDocument, Span},
+    BuilderError, ManuscriptBuilder, ParagraphLayout, RunningHeadLayout, RunningHeadSegment,
+};
+use crate::{config::NotePlacement, extensions::EscapeXml, story::Story, word_count::WordCount};
+
+const ODT_MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+const ODT_NAMESPACES: &str = r#"xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" xmlns:svg="urn:oasis:names:tc:opendocument:xmlns:svg-compatible:1.0" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/""#;
+const FIRST_MASTER_PAGE: &str = "First_20_Page";
+
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.3">
+  <manifest:file-entry manifest:full-path="/" manifest:version="1.3" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+  <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+  <manifest:file-entry manifest:full-path="styles.xml" manifest:media-type="text/xml"/>
+  <manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+/// Escapes text into the content of a paragraph. Consecutive, leading and trailing spaces are
+/// collapsed by OpenDocument readers, so they're written as `<text:s>` elements.
+fn odt_text(text: &str) -> String {
+    escape_odt(text, true, true)
+}
+
+/// Escapes a part of a paragraph's content, where `at_start` and `at_end` tell whether it's at
+/// the start or the end of the paragraph.
+fn escape_odt(text: &str, at_start: bool, at_end: bool) -> String {
+    let mut escaped = String::new();
+    let mut characters = text.chars().peekable();
+    let mut is_start = at_start;
+
+    while let Some(character) = characters.next() {
+        match character {
+            ' ' => {
+                let mut count = 1;
+                while characters.next_if_eq(&' ').is_some() {
+                    count += 1;
+                }
+                let is_end = at_end && characters.peek().is_none();
+
+                if !is_start && !is_end {
+                    escaped.push(' ');
+                    count -= 1;
+                }
+                match count {
+                    0 => (),
+                    1 => escaped.push_str("<text:s/>"),
+                    _ => escaped.push_str(&format!("<text:s text:c=\"{}\"/>", count)),
+                }
+            }
+            '\t' => escaped.push_str("<text:tab/>"),
+            _ if character.is_control() => (),
+            _ => escaped.push_str(&character.to_string().escape_xml()),
+        }
+        is_start = false;
+    }
+
+    escaped
+}
+
+/// Automatic paragraph style of the content, which is shared by every paragraph with the same
+/// layout.
+#[derive(Debug, Clone)]
+struct OdtParagraphStyle {
+    layout: ParagraphLayout,
+    space_before_point: f32,
+    break_before: bool,
+    keep_with_next: bool,
+    /// Position of a tab stop that aligns the text after it flush right.
+    right_tab_inch: Option<f32>,
+    master_page: Option<&'static str>,
+}
+
+impl OdtParagraphStyle {
+    fn new(layout: &ParagraphLayout) -> Self {
+        Self {
+            layout: layout.clone(),
+            space_before_point: 0.0,
+            break_before: false,
+            keep_with_next: false,
+            right_tab_inch: None,
+            master_page: None,
+        }
+    }
+
+    fn to_xml(&self, name: &str) -> String {
+        let alignment = match self.layout.alignment {
+            AlignmentType::Center => "center",
+            AlignmentType::Right => "end",
+            AlignmentType::Both => "justify",
+            _ => "start",
+        };
+        let master_page = self.master_page.map_or(String::new(), |master_page| {
+            format!(" style:master-page-name=\"{}\"", master_page)
+        });
+        let break_before = match self.break_before {
+            true => " fo:break-before=\"page\"",
+            false => "",
+        };
+        let keep_with_next = match self.keep_with_next {
+            true => " fo:keep-with-next=\"always\"",
+            false => "",
+        };
+        let paragraph_properties_end = self.right_tab_inch.map_or("/>".into(), |position| {
+            format!(
+                "><style:tab-stops><style:tab-stop style:position=\"{:.4}in\" style:type=\"right\"/></style:tab-stops></style:paragraph-properties>",
+                position
+            )
+        });
+
+        format!(
+            "    <style:style style:name=\"{}\" style:family=\"paragraph\" style:parent-style-name=\"Standard\"{}>\n      <style:paragraph-properties fo:text-align=\"{}\" fo:text-indent=\"{:.4}in\" fo:margin-left=\"{:.4}in\" fo:margin-right=\"{:.4}in\" fo:line-height=\"{:.2}pt\" fo:margin-top=\"{:.2}pt\" fo:margin-bottom=\"{:.2}pt\"{}{}{}\n      <style:text-properties style:font-name=\"{}\" fo:font-size=\"{:.1}pt\"/>\n    </style:style>\n",
+            name,
+            master_page,
+            alignment,
+            self.layout.first_line_indention_inch,
+            self.layout.left_indention_inch,
+            self.layout.right_indention_inch,
+            self.layout.line_spacing_point,
+            self.space_before_point,
+            self.layout.after_line_spacing_point,
+            break_before,
+            keep_with_next,
+            paragraph_properties_end,
+            self.layout.font_family.escape_xml(),
+            self.layout.font_size_point
+        )
+    }
+}
+
+/// Writes the paragraphs of the content while collecting their automatic styles.
+#[derive(Debug, Default)]
+struct OdtContent {
+    styles: Vec<OdtParagraphStyle>,
+    body: String,
+    note_count: usize,
+}
+
+impl OdtContent {
+    fn style_name(&mut self, style: OdtParagraphStyle) -> String {
+        let style_xml = style.to_xml("");
+        let index = match self
+            .styles
+            .iter()
+            .position(|existing| existing.to_xml("") == style_xml)
+        {
+            Some(index) => index,
+            None => {
+                self.styles.push(style);
+                self.styles.len() - 1
+            }
+        };
+
+        format!("P{}", index + 1)
+    }
+
+    fn push_paragraph(&mut self, content: &str, mut style: OdtParagraphStyle) {
+        if self.body.is_empty() && style.master_page.is_none() {
+            style.master_page = Some(FIRST_MASTER_PAGE);
+        }
+
+        let style_name = self.style_name(style);
+        self.body.push_str(&format!(
+            "      <text:p text:style-name=\"{}\">{}</text:p>\n",
+            style_name, content
+        ));
+    }
+}
+
+impl ManuscriptBuilder {
+    fn odt_running_head(&self, element: &str, running_head: &RunningHeadLayout) -> String {
+        let content: String = running_head
+            .segments(&self.layout)
+            .iter()
+            .map(|segment| match segment {
+                RunningHeadSegment::Text(text) => odt_text(text),
+                RunningHeadSegment::PageNumber => {
+                    "<text:page-number text:select-page=\"current\">1</text:page-number>".into()
+                }
+                RunningHeadSegment::PageCount => "<text:page-count>1</text:page-count>".into(),
+            })
+            .collect();
+        let style_name = match element {
+            "header" => "Header",
+            _ => "Footer",
+        };
+
+        format!(
+            "      <style:{element}><text:p text:style-name=\"{}\">{}</text:p></style:{element}>\n",
+            style_name,
+            content,
+            element = element
+        )
+    }
+
+    fn odt_running_head_style(&self, running_head: &RunningHeadLayout, name: &str) -> String {
+        let alignment = match running_head.alignment {
+            AlignmentType::Center => "center",
+            AlignmentType::Right => "end",
+            _ => "start",
+        };
+
+        format!(
+            "    <style:style style:name=\"{}\" style:family=\"paragraph\" style:parent-style-name=\"Standard\">\n      <style:paragraph-properties fo:text-align=\"{}\" fo:text-indent=\"0in\" fo:line-height=\"{:.2}pt\"/>\n    </style:style>\n",
+            name, alignment, self.layout.font_size_point
+        )
+    }
+
+    fn odt_styles(&self) -> String {
+        let page = &self.layout.page;
+        let font_size_inch = self.layout.font_size_point / 72.0;
+        let header = self.layout.header.as_ref();
+        let footer = self.layout.footer.as_ref();
+
+        // The header and footer are placed halfway into the margins, and take up the rest of
+        // the margins with their spacing from the body.
+        let margin_top = match header {
+            Some(_) => page.margin_top_inch / 2.0,
+            None => page.margin_top_inch,
+        };
+        let margin_bottom = match footer {
+            Some(_) => page.margin_bottom_inch / 2.0,
+            None => page.margin_bottom_inch,
+        };
+        let header_style = header.map_or(String::new(), |_| {
+            format!(
+                "      <style:header-style><style:header-footer-properties fo:min-height=\"0in\" fo:margin-bottom=\"{:.4}in\"/></style:header-style>\n",
+                (page.margin_top_inch / 2.0 - font_size_inch).max(0.0)
+            )
+        });
+        let footer_style = footer.map_or(String::new(), |_| {
+            format!(
+                "      <style:footer-style><style:header-footer-properties fo:min-height=\"0in\" fo:margin-top=\"{:.4}in\"/></style:footer-style>\n",
+                (page.margin_bottom_inch / 2.0 - font_size_inch).max(0.0)
+            )
+        });
+
+        let running_heads = |first_page: bool| -> String {
+            [("header", header), ("footer", footer)]
+                .into_iter()
+                .filter_map(|(element, running_head)| {
+                    running_head
+                        .filter(|running_head| !first_page || running_head.first_page)
+                        .map(|running_head| self.odt_running_head(element, running_head))
+                })
+                .collect()
+        };
+
+        let running_head_styles: String = [("Header", header), ("Footer", footer)]
+            .into_iter()
+            .filter_map(|(name, running_head)| {
+                running_head.map(|running_head| self.odt_running_head_style(running_head, name))
+            })
+            .collect();
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-styles {namespaces} office:version="1.3">
+  <office:font-face-decls>
+    <style:font-face style:name="{font}" svg:font-family="&apos;{font}&apos;"/>
+  </office:font-face-decls>
+  <office:styles>
+    <style:style style:name="Standard" style:family="paragraph">
+      <style:paragraph-properties fo:line-height="{line_height:.2}pt" fo:margin-top="0pt" fo:margin-bottom="0pt" fo:widows="{widows}" fo:orphans="{widows}"/>
+      <style:text-properties style:font-name="{font}" fo:font-size="{font_size:.1}pt" fo:language="{language}"/>
+    </style:style>
+{running_head_styles}  </office:styles>
+  <office:automatic-styles>
+    <style:page-layout style:name="pm1">
+      <style:page-layout-properties fo:page-width="{width:.4}in" fo:page-height="{height:.4}in" fo:margin-top="{margin_top:.4}in" fo:margin-bottom="{margin_bottom:.4}in" fo:margin-left="{margin_left:.4}in" fo:margin-right="{margin_right:.4}in"/>
+{header_style}{footer_style}    </style:page-layout>
+  </office:automatic-styles>
+  <office:master-styles>
+    <style:master-page style:name="Standard" style:page-layout-name="pm1">
+{standard_running_heads}    </style:master-page>
+    <style:master-page style:name="{first_master_page}" style:display-name="First Page" style:page-layout-name="pm1" style:next-style-name="Standard">
+{first_running_heads}    </style:master-page>
+  </office:master-styles>
+</office:document-styles>
+"#,
+            namespaces = ODT_NAMESPACES,
+            font = self.layout.font_family.escape_xml(),
+            line_height = self.layout.font_size_point,
+            font_size = self.layout.font_size_point,
+            language = self.layout.language.escape_xml(),
+            widows = match self.layout.pagination.widow_control {
+                true => 2,
+                false => 0,
+            },
+            running_head_styles = running_head_styles,
+            width = page.width_inch,
+            height = page.height_inch,
+            margin_top = margin_top,
+            margin_bottom = margin_bottom,
+            margin_left = page.margin_left_inch,
+            margin_right = page.margin_right_inch,
+            header_style = header_style,
+            footer_style = footer_style,
+            standard_running_heads = running_heads(false),
+            first_master_page = FIRST_MASTER_PAGE,
+            first_running_heads = running_heads(true),
+        )
+    }
+
+    fn odt_meta(&self, word_count: u32) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta {} office:version="1.3">
+  <office:meta>
+    <dc:title>{}</dc:title>
+    <dc:creator>{}</dc:creator>
+    <dc:language>{}</dc:language>
+    <meta:generator>makinilya/{}</meta:generator>
+    <meta:document-statistic meta:word-count="{}"/>
+  </office:meta>
+</office:document-meta>
+"#,
+            ODT_NAMESPACES,
+            self.layout.title.escape_xml(),
+            self.layout.pen_name.escape_xml(),
+            self.layout.language.escape_xml(),
+            env!("CARGO_PKG_VERSION"),
+            word_count
+        )
+    }
+
+    fn odt_title_page(&self, content: &mut OdtContent, word_count: u32) {
+        let title_page = self.layout.title_page_layout(word_count);
+        let blocks = [
+            (
+                &title_page.middle_lines,
+                &title_page.middle_layout,
+                title_page.middle_space_before_point,
+            ),
+            (
+                &title_page.bottom_lines,
+                &title_page.bottom_layout,
+                title_page.bottom_space_before_point,
+            ),
+        ];
+
+        for (line, right_line) in title_page.top_rows() {
+            let mut style = OdtParagraphStyle::new(&title_page.top_layout);
+            let text = match right_line {
+                Some(right_line) => {
+                    style.right_tab_inch = Some(self.layout.page.content_width_inch());
+                    format!("{}<text:tab/>{}", odt_text(line), odt_text(right_line))
+                }
+                None => odt_text(line),
+            };
+            content.push_paragraph(&text, style);
+        }
+
+        for (lines, layout, space_before) in blocks {
+            for (index, line) in lines.iter().enumerate() {
+                let mut style = OdtParagraphStyle::new(layout);
+                if index == 0 {
+                    style.space_before_point = space_before;
+                }
+                content.push_paragraph(&odt_text(line), style);
+            }
+        }
+    }
+
+    /// Escapes the text of a paragraph, with its emphasis in the `Emphasis` style and its notes
+    /// as OpenDocument notes.
+    fn odt_inline(&self, content: &mut OdtContent, text: &str) -> String {
+        let spans = Span::parse(text);
+        let last_index = spans.len().saturating_sub(1);
+
+        spans
+            .iter()
+            .enumerate()
+            .map(|(index, span)| match span {
+                Span::Text(text) => escape_odt(text, index == 0, index == last_index),
+                Span::Emphasis(text) => format!(
+                    "<text:span text:style-name=\"Emphasis\">{}</text:span>",
+                    escape_odt(text, index == 0, index == last_index)
+                ),
+                Span::Note(note) => {
+                    content.note_count += 1;
+                    let number = content.note_count;
+                    let note_style = content.style_name(OdtParagraphStyle::new(&ParagraphLayout {
+                        line_spacing_point: self.layout.font_size_point,
+                        ..self.layout.paragraph_layout()
+                    }));
+                    format!(
+                        "<text:note text:id=\"note{number}\" text:note-class=\"{class}\"><text:note-citation>{number}</text:note-citation><text:note-body><text:p text:style-name=\"{style}\">{text}</text:p></text:note-body></text:note>",
+                        number = number,
+                        class = match self.layout.notes {
+                            NotePlacement::Footnotes => "footnote",
+                            NotePlacement::Endnotes => "endnote",
+                        },
+                        style = note_style,
+                        text = self.odt_inline(content, note)
+                    )
+                }
+            })
+            .collect()
+    }
+
+    fn odt_block(&self, content: &mut OdtContent, block: &Block) {
+        let layout = &self.layout;
+
+        match block {
+            Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {
+                let title = block
+                    .heading_lines()
+                    .iter()
+                    .map(|line| odt_text(line))
+                    .collect::<Vec<String>>()
+                    .join("<text:line-break/>");
+                let mut style = OdtParagraphStyle::new(&ParagraphLayout {
+                    after_line_spacing_point: layout.line_spacing_point(),
+                    alignment: AlignmentType::Center,
+                    ..layout.paragraph_layout()
+                });
+                style.space_before_point = layout.page.content_height_inch() * 72.0 / 3.0;
+                style.break_before = true;
+                style.keep_with_next = layout.pagination.keep_with_next;
+                content.push_paragraph(&title, style);
+            }
+            Block::Paragraph(text)
+            | Block::FirstParagraph(text)
+            | Block::CenteredParagraph(text)
+            | Block::BlockQuote(text) => {
+                let spans = self.odt_inline(content, text);
+                let style = OdtParagraphStyle::new(&match block {
+                    Block::CenteredParagraph(_) => ParagraphLayout {
+                        alignment: AlignmentType::Center,
+                        ..layout.paragraph_layout()
+                    },
+                    Block::FirstParagraph(_) => layout.paragraph_layout(),
+                    Block::BlockQuote(_) => layout.block_quote_layout(),
+                    _ => ParagraphLayout {
+                        first_line_indention_inch: layout.first_line_indention_inch,
+                        ..layout.paragraph_layout()
+                    },
+                });
+                content.push_paragraph(&spans, style);
+            }
+            Block::Image(image) => {
+                if let Some(placeholder) = Self::image_placeholder(image) {
+                    let style = OdtParagraphStyle::new(&ParagraphLayout {
+                        alignment: image.alignment.into(),
+                        ..layout.paragraph_layout()
+                    });
+                    content.push_paragraph(&odt_text(&placeholder), style);
+                }
+            }
+            Block::SceneBreak => {
+                let mut style = OdtParagraphStyle::new(&ParagraphLayout {
+                    alignment: AlignmentType::Center,
+                    ..layout.paragraph_layout()
+                });
+                style.keep_with_next = layout.pagination.keep_with_next;
+                content.push_paragraph(&odt_text(&layout.scene_break), style);
+            }
+        }
+    }
+
+    fn odt_content(&self, document: &ManuscriptDocument, word_count: u32) -> String {
+        let mut content = OdtContent::default();
+
+        self.odt_title_page(&mut content, word_count);
+        for block in self.paged_blocks(document) {
+            self.odt_block(&mut content, block);
+        }
+
+        let styles: String = content
+            .styles
+            .iter()
+            .enumerate()
+            .map(|(index, style)| style.to_xml(&format!("P{}", index + 1)))
+            .collect();
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content {namespaces} office:version="1.3">
+  <office:font-face-decls>
+    <style:font-face style:name="{font}" svg:font-family="&apos;{font}&apos;"/>
+  </office:font-face-decls>
+  <office:automatic-styles>
+{styles}    <style:style style:name="Emphasis" style:family="text">
+      <style:text-properties fo:font-style="italic"/>
+    </style:style>
+  </office:automatic-styles>
+  <office:body>
+    <office:text>
+{body}    </office:text>
+  </office:body>
+</office:document-content>
+"#,
+            namespaces = ODT_NAMESPACES,
+            font = self.layout.font_family.escape_xml(),
+            styles = styles,
+            body = content.body
+        )
+    }
+
+    pub fn build_odt(&self, story: &Story) -> Result<Vec<u8>, BuilderError> {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let word_count = WordCount::count_document(&document);
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(ODT_MIMETYPE.as_bytes())?;
+        zip.start_file("META-INF/manifest.xml", deflated)?;
+        zip.write_all(MANIFEST.as_bytes())?;
+        zip.start_file("content.xml", deflated)?;
+        zip.write_all(self.odt_content(&document, word_count).as_bytes())?;
+        zip.start_file("styles.xml", deflated)?;
+        zip.write_all(self.odt_styles().as_bytes())?;
+        zip.start_file("meta.xml", deflated)?;
+        zip.write_all(self.odt_meta(word_count).as_bytes())?;
+
+        Ok(zip.finish()?.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod odt_tests {
+    use std::io::Read;
+
+    use zip::ZipArchive;
+
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn escapes_odt_text() {
+        assert_eq!(odt_text("a  b"), "a <text:s/>b");
+        assert_eq!(odt_text(" <a>\t"), "<text:s/>&lt;a&gt;<text:tab/>");
+        assert_eq!(odt_text("a   "), "a<text:s text:c=\"3\"/>");
+    }
+
+    #[test]
+    fn builds_odt() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I have *never* been to the sea.");
+        chapter_1.push_content("I am Scene #2.^[Or #3.]");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let odt = builder.build_odt(&story).unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(odt)).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+
+        let mut content = String::new();
+        archive
+            .by_name("content.xml")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert!(content.contains(
+            "I have <text:span text:style-name=\"Emphasis\">never</text:span> been to the sea."
+        ));
+        assert!(content.contains("I am Scene #2.<text:note text:id=\"note1\" text:note-class=\"footnote\"><text:note-citation>1</text:note-citation>"));
+        assert!(content.contains("style:master-page-name=\"First_20_Page\""));
+        assert!(content.contains("fo:break-before=\"page\" fo:keep-with-next=\"always\""));
+
+        let mut styles = String::new();
+        archive
+            .by_name("styles.xml")
+            .unwrap()
+            .read_to_string(&mut styles)
+            .unwrap();
+        assert!(styles.contains("<text:page-number text:select-page=\"current\">"));
+        assert!(styles.contains("fo:widows=\"2\" fo:orphans=\"2\""));
+    }
+}
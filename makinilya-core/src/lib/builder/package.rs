@@ -0,0 +1,145 @@
+//! Packaging of the word document with the parts that `docx_rs` doesn't write on its own.
+//!
+//! A part is related to the main document, so its content type and relationship are registered
+//! with the package when it's added.
+
+use std::io::{Cursor, Seek, Write};
+
+use docx_rs::XMLDocx;
+use zip::{result::ZipResult, write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// Part of the word document that's written after the parts of `docx_rs`.
+#[derive(Debug, Clone)]
+struct DocxPart {
+    path: String,
+    content: Vec<u8>,
+}
+
+/// Word document whose parts are packed into a single file.
+pub struct DocxPackage {
+    pub docx: XMLDocx,
+    parts: Vec<DocxPart>,
+}
+
+impl DocxPackage {
+    pub fn new(docx: XMLDocx) -> Self {
+        Self {
+            docx,
+            parts: vec![],
+        }
+    }
+
+    /// Adds a part at `path`, e.g. `word/footnotes.xml`, that the main document refers to
+    /// through a relationship of `relationship_type`. Returns the id of the relationship.
+    pub fn add_document_part(
+        &mut self,
+        path: &str,
+        content_type: &str,
+        relationship_type: &str,
+        content: Vec<u8>,
+    ) -> String {
+        let id = format!("rIdPart{}", self.parts.len() + 1);
+        let target = path.strip_prefix("word/").unwrap_or(path);
+
+        insert_before(
+            &mut self.docx.content_type,
+            "</Types>",
+            &format!(
+                r#"<Override PartName="/{}" ContentType="{}" />"#,
+                path, content_type
+            ),
+        );
+        insert_before(
+            &mut self.docx.document_rels,
+            "</Relationships>",
+            &format!(
+                r#"<Relationship Id="{}" Type="{}" Target="{}" />"#,
+                id, relationship_type, target
+            ),
+        );
+
+        self.parts.push(DocxPart {
+            path: path.to_string(),
+            content,
+        });
+
+        id
+    }
+
+    /// Writes the package as a zip archive, where the added parts follow the parts of
+    /// `docx_rs`.
+    pub fn pack<W: Write + Seek>(self, writer: W) -> ZipResult<()> {
+        if self.parts.is_empty() {
+            return self.docx.pack(writer);
+        }
+
+        let mut base = Cursor::new(vec![]);
+        self.docx.pack(&mut base)?;
+        let mut archive = ZipArchive::new(base)?;
+        let mut zip = ZipWriter::new(writer);
+
+        for index in 0..archive.len() {
+            zip.raw_copy_file(archive.by_index_raw(index)?)?;
+        }
+
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        for part in self.parts {
+            zip.start_file(part.path, options)?;
+            zip.write_all(&part.content)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+fn insert_before(xml: &mut Vec<u8>, closing_tag: &str, element: &str) {
+    let text = String::from_utf8_lossy(xml);
+    if let Some(end) = text.rfind(closing_tag) {
+        let mut text = text.to_string();
+        text.insert_str(end, element);
+        *xml = text.into_bytes();
+    }
+}
+
+#[cfg(test)]
+mod package_tests {
+    use std::io::Read;
+
+    use docx_rs::Docx;
+
+    use super::*;
+
+    #[test]
+    fn packs_document_parts() {
+        let mut package = DocxPackage::new(Docx::new().build());
+        let id = package.add_document_part(
+            "word/footnotes.xml",
+            "application/test+xml",
+            "http://example/footnotes",
+            b"<w:footnotes/>".to_vec(),
+        );
+        assert_eq!(id, "rIdPart1");
+
+        let mut packed = Cursor::new(vec![]);
+        package.pack(&mut packed).unwrap();
+        let mut archive = ZipArchive::new(packed).unwrap();
+
+        let mut read = |path: &str| {
+            let mut content = String::new();
+            archive
+                .by_name(path)
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            content
+        };
+        assert_eq!(read("word/footnotes.xml"), "<w:footnotes/>");
+        assert!(read("[Content_Types].xml").contains(
+            r#"<Override PartName="/word/footnotes.xml" ContentType="application/test+xml" /></Types>"#
+        ));
+        assert!(read("word/_rels/document.xml.rels").contains(
+            r#"<Relationship Id="rIdPart1" Type="http://example/footnotes" Target="footnotes.xml" />"#
+        ));
+    }
+}
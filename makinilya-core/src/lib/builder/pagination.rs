@@ -0,0 +1,75 @@
+//! Page breaks before the headings of the word document.
+//!
+//! A heading that starts on a recto page needs a section break, which `docx_rs` only writes with
+//! page geometry of its own. The break is written as a run of a marker text instead, which is
+//! replaced with a copy of the document's section properties once the document is built.
+
+use docx_rs::{BreakType, Paragraph, Run};
+
+use super::{package::DocxPackage, ManuscriptBuilder};
+use crate::config::ChapterStart;
+
+/// Private use character that's the text of a section break's paragraph.
+const DOCX_SECTION_BREAK_MARKER: char = '\u{E001}';
+
+const SECTION_PROPERTIES_START: &str = "<w:sectPr>";
+const SECTION_PROPERTIES_END: &str = "</w:sectPr>";
+const TITLE_PAGE_PROPERTY: &str = "<w:titlePg />";
+const ODD_PAGE_PROPERTY: &str = r#"<w:type w:val="oddPage" />"#;
+
+impl ManuscriptBuilder {
+    /// The paragraph that ends the page before a heading, which is a section break when the
+    /// heading starts on a recto page.
+    pub(crate) fn docx_page_break_paragraph(&self) -> Paragraph {
+        match self.layout.pagination.chapter_start {
+            ChapterStart::NextPage => {
+                Paragraph::new().add_run(Run::new().add_break(BreakType::Page))
+            }
+            ChapterStart::Recto => {
+                Paragraph::new().add_run(Run::new().add_text(DOCX_SECTION_BREAK_MARKER.to_string()))
+            }
+        }
+    }
+
+    /// Replaces the marker texts of the section breaks with the properties of the sections they
+    /// end. The first section holds the title page and keeps its distinct first page, while the
+    /// sections after it start on the next odd page.
+    pub(crate) fn add_docx_section_breaks(&self, package: &mut DocxPackage) {
+        let marker = format!(
+            r#"<w:rPr /></w:pPr><w:r><w:rPr /><w:t xml:space="preserve">{}</w:t></w:r>"#,
+            DOCX_SECTION_BREAK_MARKER
+        );
+        let document = String::from_utf8_lossy(&package.docx.document).to_string();
+        if !document.contains(&marker) {
+            return;
+        }
+        let (Some(start), Some(end)) = (
+            document.rfind(SECTION_PROPERTIES_START),
+            document.rfind(SECTION_PROPERTIES_END),
+        ) else {
+            return;
+        };
+
+        let first_section = &document[start..end + SECTION_PROPERTIES_END.len()];
+        let recto_section = first_section.replace(TITLE_PAGE_PROPERTY, "").replacen(
+            "<w:pgSz",
+            &format!("{}<w:pgSz", ODD_PAGE_PROPERTY),
+            1,
+        );
+
+        let mut sections = document[..start].split(&marker);
+        let mut built = sections.next().unwrap_or_default().to_string();
+        for (index, section) in sections.enumerate() {
+            let properties = match index {
+                0 => first_section,
+                _ => &recto_section,
+            };
+            built.push_str(&format!("<w:rPr />{}</w:pPr>", properties));
+            built.push_str(section);
+        }
+        built.push_str(&recto_section);
+        built.push_str(&document[end + SECTION_PROPERTIES_END.len()..]);
+
+        package.docx.document = built.into_bytes();
+    }
+}
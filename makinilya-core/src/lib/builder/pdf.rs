@@ -0,0 +1,1285 @@
+//! Portable Document Format (PDF) output of the manuscript.
+//!
+//! The document is typeset with the standard Type 1 fonts (Times, Helvetica and Courier) that
+//! every conforming PDF reader bundles, so building it requires neither installed fonts nor an
+//! office suite. The `font_family` of the layout is mapped to the closest standard family, whose
+//! italic face sets the emphasis. The standard fonts only cover `WinAnsiEncoding`, so a text
+//! with any other character, such as Greek or Cyrillic, is typeset with the bundled DejaVu Serif
+//! instead, whose used glyphs are embedded in the document. A character that neither covers,
+//! such as a CJK ideograph, fails to build.
+//!
+//! Notes are numbered in brackets, e.g. `[1]`, since the text is not laid out around footnotes.
+//! Footnotes are listed at the end of their chapter instead, and endnotes on their own pages at
+//! the end of the manuscript.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use docx_rs::AlignmentType;
+use subsetter::Profile;
+use ttf_parser::{Face, GlyphId};
+
+use super::{
+    document::{Block, ManuscriptDocument, Notes, Span},
+    BuilderError, ManuscriptBuilder, ManuscriptBuilderLayout, ParagraphLayout, RunningHeadLayout,
+    RunningHeadSegment,
+};
+use crate::{
+    config::{ChapterStart, NotePlacement},
+    story::Story,
+    word_count::WordCount,
+};
+
+const POINTS_PER_INCH: f32 = 72.0;
+
+/// The faces that typeset a text which the standard fonts can't encode.
+const SERIF_FONT: &[u8] = include_bytes!("../../../fonts/DejaVuSerif.ttf");
+const SERIF_ITALIC_FONT: &[u8] = include_bytes!("../../../fonts/DejaVuSerif-Italic.ttf");
+
+/// Advance widths of the printable ASCII characters (32 to 126) in thousandths of an em.
+const TIMES_ROMAN_WIDTHS: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444, 921, 722, 667, 667, 722, 611,
+    556, 722, 722, 333, 389, 722, 611, 889, 722, 722, 556, 722, 667, 556, 611, 722, 722, 944, 722,
+    722, 611, 333, 278, 333, 469, 500, 333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500,
+    278, 778, 500, 500, 500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+
+const TIMES_ITALIC_WIDTHS: [u16; 95] = [
+    250, 333, 420, 500, 500, 833, 778, 214, 333, 333, 500, 675, 250, 333, 250, 278, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 333, 333, 675, 675, 675, 500, 920, 611, 611, 667, 722, 611,
+    611, 722, 722, 333, 444, 667, 556, 833, 667, 722, 611, 722, 611, 500, 556, 722, 611, 833, 611,
+    556, 556, 389, 278, 389, 422, 500, 333, 500, 500, 444, 500, 444, 278, 500, 500, 278, 278, 444,
+    278, 722, 500, 500, 500, 500, 389, 389, 278, 500, 444, 667, 444, 444, 389, 400, 275, 400, 541,
+];
+
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StandardFont {
+    Times,
+    Helvetica,
+    Courier,
+}
+
+impl StandardFont {
+    pub fn from_family(family: &str) -> Self {
+        let family = family.to_lowercase();
+
+        if family.contains("courier") || family.contains("mono") {
+            Self::Courier
+        } else if ["helvetica", "arial", "sans"]
+            .iter()
+            .any(|name| family.contains(name))
+        {
+            Self::Helvetica
+        } else {
+            Self::Times
+        }
+    }
+
+    pub fn base_font(&self) -> &'static str {
+        match self {
+            Self::Times => "Times-Roman",
+            Self::Helvetica => "Helvetica",
+            Self::Courier => "Courier",
+        }
+    }
+
+    pub fn italic_base_font(&self) -> &'static str {
+        match self {
+            Self::Times => "Times-Italic",
+            Self::Helvetica => "Helvetica-Oblique",
+            Self::Courier => "Courier-Oblique",
+        }
+    }
+
+    /// Width of a `WinAnsiEncoding` character in thousandths of an em.
+    pub fn width(&self, byte: u8, italic: bool) -> u16 {
+        let widths = match (self, italic) {
+            (Self::Courier, _) => return 600,
+            (Self::Times, false) => &TIMES_ROMAN_WIDTHS,
+            (Self::Times, true) => &TIMES_ITALIC_WIDTHS,
+            (Self::Helvetica, _) => &HELVETICA_WIDTHS,
+        };
+
+        match (self, italic, byte) {
+            (_, _, 32..=126) => widths[(byte - 32) as usize],
+            (_, _, 0xa0) => widths[0],
+            (Self::Times, true, 0x85 | 0x97) => 889,
+            (_, _, 0x85 | 0x97) => 1000,
+            (_, _, 0x95) => 350,
+            (Self::Times, _, 0x91 | 0x92) => 333,
+            (Self::Times, false, 0x93 | 0x94) => 444,
+            (Self::Times, true, 0x93 | 0x94) => 556,
+            (Self::Helvetica, _, 0x91 | 0x92) => 222,
+            (Self::Helvetica, _, 0x93 | 0x94) => 333,
+            (Self::Times, _, _) => 500,
+            (_, _, _) => 556,
+        }
+    }
+}
+
+/// Encodes a character to `WinAnsiEncoding`, the single-byte encoding of the standard fonts.
+/// The hyphens and spaces that the encoding lacks are set as their closest equivalent.
+fn encode_char(character: char) -> Option<u8> {
+    let byte = match character {
+        '\t' | '\u{2000}'..='\u{2006}' | '\u{2008}'..='\u{200a}' => b' ',
+        '\u{2007}' | '\u{202f}' => 0xa0,
+        '\u{2010}' | '\u{2011}' => b'-',
+        ' '..='~' => character as u8,
+        '\u{a0}'..='\u{ff}' => character as u32 as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    };
+
+    Some(byte)
+}
+
+/// The characters of a text that are typeset, i.e. without control and zero-width characters.
+fn printable_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars().filter(|character| {
+        (!character.is_control() || *character == '\t')
+            && !matches!(character, '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}')
+    })
+}
+
+fn encode(text: &str) -> Result<Vec<u8>, BuilderError> {
+    printable_chars(text)
+        .map(|character| {
+            encode_char(character).ok_or(BuilderError::UnencodableCharacter(character))
+        })
+        .collect()
+}
+
+/// A bundled TrueType face, whose glyphs are set by their ids.
+#[derive(Clone)]
+struct EmbeddedFace {
+    name: &'static str,
+    data: &'static [u8],
+    face: Face<'static>,
+}
+
+impl EmbeddedFace {
+    fn new(name: &'static str, data: &'static [u8]) -> Self {
+        Self {
+            name,
+            data,
+            face: Face::parse(data, 0).expect("bundled font is a valid TrueType font"),
+        }
+    }
+
+    /// Scales a length in font units to thousandths of an em.
+    fn scale(&self, length: f32) -> i32 {
+        (length * 1000.0 / self.face.units_per_em() as f32).round() as i32
+    }
+
+    fn glyph_id(&self, character: char) -> Option<u16> {
+        self.face.glyph_index(character).map(|glyph_id| glyph_id.0)
+    }
+
+    fn width(&self, glyph_id: u16) -> u16 {
+        let advance = self.face.glyph_hor_advance(GlyphId(glyph_id));
+        self.scale(advance.unwrap_or_default() as f32) as u16
+    }
+
+    /// The characters of the glyphs, from which text is extracted out of the document.
+    fn characters(&self, glyph_ids: &BTreeSet<u16>) -> BTreeMap<u16, char> {
+        let mut characters = BTreeMap::new();
+        let subtables = self
+            .face
+            .tables()
+            .cmap
+            .into_iter()
+            .flat_map(|cmap| cmap.subtables);
+
+        for subtable in subtables.filter(|subtable| subtable.is_unicode()) {
+            subtable.codepoints(|codepoint| {
+                let glyph_id = subtable.glyph_index(codepoint).map(|glyph_id| glyph_id.0);
+                let character = char::from_u32(codepoint);
+                if let (Some(glyph_id), Some(character)) = (glyph_id, character) {
+                    if glyph_ids.contains(&glyph_id) {
+                        characters
+                            .entry(glyph_id)
+                            .and_modify(|known: &mut char| *known = character.min(*known))
+                            .or_insert(character);
+                    }
+                }
+            });
+        }
+
+        characters
+    }
+}
+
+/// The fonts that the manuscript is typeset with.
+#[derive(Clone)]
+enum PdfFont {
+    Standard(StandardFont),
+    /// The bundled regular and italic faces.
+    Embedded(Box<(EmbeddedFace, EmbeddedFace)>),
+}
+
+impl PdfFont {
+    fn embedded() -> Self {
+        Self::Embedded(Box::new((
+            EmbeddedFace::new("DejaVuSerif", SERIF_FONT),
+            EmbeddedFace::new("DejaVuSerif-Italic", SERIF_ITALIC_FONT),
+        )))
+    }
+
+    fn embedded_face(&self, italic: bool) -> Option<&EmbeddedFace> {
+        match (self, italic) {
+            (Self::Standard(_), _) => None,
+            (Self::Embedded(faces), false) => Some(&faces.0),
+            (Self::Embedded(faces), true) => Some(&faces.1),
+        }
+    }
+
+    /// Encodes a text to the codes of the font, which are `WinAnsiEncoding` bytes for a
+    /// standard font and glyph ids for an embedded one.
+    fn encode(&self, text: &str, italic: bool) -> Result<Vec<u16>, BuilderError> {
+        let Some(face) = self.embedded_face(italic) else {
+            return Ok(encode(text)?.into_iter().map(u16::from).collect());
+        };
+
+        printable_chars(text)
+            .map(|character| {
+                let glyph_id = match character.is_whitespace() {
+                    true => face.glyph_id(character).or(face.glyph_id(' ')),
+                    false => face.glyph_id(character),
+                };
+                glyph_id.ok_or(BuilderError::UnencodableCharacter(character))
+            })
+            .collect()
+    }
+
+    fn width(&self, code: u16, italic: bool) -> u16 {
+        match (self, self.embedded_face(italic)) {
+            (Self::Standard(font), _) => font.width(code as u8, italic),
+            (_, Some(face)) => face.width(code),
+            (_, None) => 0,
+        }
+    }
+
+    fn space(&self, italic: bool) -> u16 {
+        match self.embedded_face(italic) {
+            Some(face) => face.glyph_id(' ').unwrap_or_default(),
+            None => b' '.into(),
+        }
+    }
+
+    /// Writes the codes of a text as a PDF string.
+    fn string(&self, codes: &[u16]) -> String {
+        match self {
+            Self::Standard(_) => {
+                literal_string(&codes.iter().map(|code| *code as u8).collect::<Vec<u8>>())
+            }
+            Self::Embedded(_) => hex_string(codes),
+        }
+    }
+}
+
+/// Encoded character of a line, and whether it's set in the italic face.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Glyph {
+    code: u16,
+    italic: bool,
+}
+
+/// Writes an encoded string as a PDF literal string, escaping delimiters and non-ASCII bytes.
+fn literal_string(bytes: &[u8]) -> String {
+    let mut literal = String::from("(");
+
+    for byte in bytes {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                literal.push('\\');
+                literal.push(*byte as char);
+            }
+            32..=126 => literal.push(*byte as char),
+            _ => literal.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+
+    literal.push(')');
+    literal
+}
+
+/// Writes two-byte codes as a PDF hexadecimal string.
+fn hex_string(codes: &[u16]) -> String {
+    let hex: String = codes.iter().map(|code| format!("{:04X}", code)).collect();
+    format!("<{}>", hex)
+}
+
+/// Writes a text as a PDF text string in UTF-16, e.g. for the properties of the document.
+fn text_string(text: &str) -> String {
+    hex_string(
+        &[
+            &[0xfeff],
+            text.encode_utf16().collect::<Vec<u16>>().as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+#[derive(Debug)]
+struct PositionedText {
+    x: f32,
+    y: f32,
+    size: f32,
+    text: Vec<u16>,
+    italic: bool,
+}
+
+#[derive(Debug, Default)]
+struct PdfPage {
+    texts: Vec<PositionedText>,
+}
+
+/// Lays the blocks of the manuscript out on pages. The cursor is the distance in points from
+/// the top of the current page's content area.
+struct PdfLayouter<'a> {
+    builder: &'a ManuscriptBuilder,
+    font: PdfFont,
+    pages: Vec<PdfPage>,
+    cursor: f32,
+    notes: Notes,
+}
+
+impl<'a> PdfLayouter<'a> {
+    fn new(builder: &'a ManuscriptBuilder, font: PdfFont) -> Self {
+        Self {
+            builder,
+            font,
+            pages: vec![PdfPage::default()],
+            cursor: 0.0,
+            notes: Notes::default(),
+        }
+    }
+
+    fn page_height(&self) -> f32 {
+        self.builder.layout.page.height_inch * POINTS_PER_INCH
+    }
+
+    fn content_width(&self) -> f32 {
+        self.builder.layout.page.content_width_inch() * POINTS_PER_INCH
+    }
+
+    fn content_height(&self) -> f32 {
+        self.builder.layout.page.content_height_inch() * POINTS_PER_INCH
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(PdfPage::default());
+        self.cursor = 0.0;
+    }
+
+    /// Starts a new page when fewer than `line_count` lines are left on the current page.
+    fn keep_lines(&mut self, line_count: usize, line_spacing_point: f32) {
+        if self.cursor > 0.0 && self.fitting_lines(line_spacing_point) < line_count {
+            self.new_page();
+        }
+    }
+
+    /// The number of lines that are left on the current page.
+    fn fitting_lines(&self, line_spacing_point: f32) -> usize {
+        ((self.content_height() - self.cursor) / line_spacing_point).max(0.0) as usize
+    }
+
+    /// The index of the line of a paragraph of `line_count` lines that's moved to the next page
+    /// so that neither its first nor its last line is left alone on a page.
+    fn widow_break(&self, line_count: usize, line_spacing_point: f32) -> Option<usize> {
+        if !self.builder.layout.pagination.widow_control || self.cursor <= 0.0 {
+            return None;
+        }
+
+        let fitting_lines = self.fitting_lines(line_spacing_point);
+        match line_count > fitting_lines {
+            true if fitting_lines == 1 => Some(0),
+            true if line_count - fitting_lines == 1 => match fitting_lines >= 3 {
+                true => Some(fitting_lines - 1),
+                false => Some(0),
+            },
+            _ => None,
+        }
+    }
+
+    fn text_width(&self, text: &[Glyph], size: f32) -> f32 {
+        let width: u32 = text
+            .iter()
+            .map(|glyph| self.font.width(glyph.code, glyph.italic) as u32)
+            .sum();
+        width as f32 * size / 1000.0
+    }
+
+    /// Greedily breaks the text into lines at spaces. A word that's wider than the line is
+    /// placed on its own line.
+    fn wrap(&self, text: &[Glyph], size: f32, first_width: f32, width: f32) -> Vec<Vec<Glyph>> {
+        let space = Glyph {
+            code: self.font.space(false),
+            italic: false,
+        };
+        let mut lines: Vec<Vec<Glyph>> = vec![];
+        let mut line: Vec<Glyph> = vec![];
+
+        for word in text
+            .split(|glyph| glyph.code == self.font.space(glyph.italic))
+            .filter(|word| !word.is_empty())
+        {
+            let available_width = match lines.is_empty() {
+                true => first_width,
+                false => width,
+            };
+            let candidate = match line.is_empty() {
+                true => word.to_vec(),
+                false => [line.as_slice(), &[space], word].concat(),
+            };
+
+            if line.is_empty() || self.text_width(&candidate, size) <= available_width {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_vec()));
+            }
+        }
+
+        lines.push(line);
+        lines
+    }
+
+    fn write_paragraph(
+        &mut self,
+        text: &str,
+        layout: &ParagraphLayout,
+    ) -> Result<(), BuilderError> {
+        self.write_spans(&[Span::Text(text.to_string())], layout)
+    }
+
+    /// Encodes the spans of a paragraph, where emphasis is set in the italic face.
+    fn encode_spans(&self, spans: &[Span]) -> Result<Vec<Glyph>, BuilderError> {
+        let mut glyphs = vec![];
+        for span in spans {
+            let italic = matches!(span, Span::Emphasis(_));
+            glyphs.extend(
+                self.font
+                    .encode(span.text(), italic)?
+                    .into_iter()
+                    .map(|code| Glyph { code, italic }),
+            );
+        }
+
+        Ok(glyphs)
+    }
+
+    fn write_spans(
+        &mut self,
+        spans: &[Span],
+        layout: &ParagraphLayout,
+    ) -> Result<(), BuilderError> {
+        let margin_left = (self.builder.layout.page.margin_left_inch + layout.left_indention_inch)
+            * POINTS_PER_INCH;
+        let margin_top = self.builder.layout.page.margin_top_inch * POINTS_PER_INCH;
+        let indent = layout.first_line_indention_inch * POINTS_PER_INCH;
+        let size = layout.font_size_point;
+        let content_width = self.content_width()
+            - (layout.left_indention_inch + layout.right_indention_inch) * POINTS_PER_INCH;
+
+        let lines = self.wrap(
+            &self.encode_spans(spans)?,
+            size,
+            content_width - indent,
+            content_width,
+        );
+        let widow_break = self.widow_break(lines.len(), layout.line_spacing_point);
+
+        for (index, line) in lines.into_iter().enumerate() {
+            if (self.cursor > 0.0
+                && self.cursor + layout.line_spacing_point > self.content_height())
+                || widow_break == Some(index)
+            {
+                self.new_page();
+            }
+
+            let line_indent = match index {
+                0 => indent,
+                _ => 0.0,
+            };
+            let line_width = self.text_width(&line, size);
+            let x = match layout.alignment {
+                AlignmentType::Center => margin_left + (content_width - line_width) / 2.0,
+                AlignmentType::Right => margin_left + content_width - line_width,
+                _ => margin_left + line_indent,
+            };
+            let y = self.page_height() - margin_top - self.cursor - size;
+
+            let mut run_x = x;
+            for run in line.chunk_by(|glyph, next| glyph.italic == next.italic) {
+                let run_width = self.text_width(run, size);
+                let page = self.pages.last_mut().expect("layouter always has a page");
+                page.texts.push(PositionedText {
+                    x: run_x,
+                    y,
+                    size,
+                    text: run.iter().map(|glyph| glyph.code).collect(),
+                    italic: run[0].italic,
+                });
+                run_x += run_width;
+            }
+
+            self.cursor += layout.line_spacing_point;
+        }
+
+        self.cursor += layout.after_line_spacing_point;
+        Ok(())
+    }
+
+    /// The spans of a paragraph, where its notes are numbered in brackets, e.g. `[1]`.
+    fn paragraph_spans(&mut self, text: &str) -> Vec<Span> {
+        Span::parse(text)
+            .into_iter()
+            .map(|span| match span {
+                Span::Note(note) => Span::Text(format!("[{}]", self.notes.push(&note))),
+                span => span,
+            })
+            .collect()
+    }
+
+    /// Writes the title page, where the story of a short story follows on the same page.
+    fn write_title_page(&mut self, word_count: u32) -> Result<(), BuilderError> {
+        let title_page = self.builder.layout.title_page_layout(word_count);
+
+        if let Some(line) = &title_page.top_right_line {
+            let right_layout = ParagraphLayout {
+                alignment: AlignmentType::Right,
+                ..title_page.top_layout.clone()
+            };
+            self.write_paragraph(line, &right_layout)?;
+            if !title_page.top_lines.is_empty() {
+                self.cursor = 0.0;
+            }
+        }
+
+        let blocks = [
+            (&title_page.top_lines, &title_page.top_layout, 0.0),
+            (
+                &title_page.middle_lines,
+                &title_page.middle_layout,
+                title_page.middle_space_before_point,
+            ),
+            (
+                &title_page.bottom_lines,
+                &title_page.bottom_layout,
+                title_page.bottom_space_before_point,
+            ),
+        ];
+        for (lines, layout, space_before) in blocks {
+            if lines.is_empty() {
+                continue;
+            }
+            self.cursor += space_before;
+            for line in lines {
+                self.write_paragraph(line, layout)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the heading of a chapter on a new page, a third of the way down. The page is the
+    /// next odd one when chapters start on recto pages.
+    fn write_heading(&mut self, lines: &[&str]) -> Result<(), BuilderError> {
+        let layout = &self.builder.layout;
+        let line_layout = ParagraphLayout {
+            alignment: AlignmentType::Center,
+            ..layout.paragraph_layout()
+        };
+        let heading_layout = ParagraphLayout {
+            after_line_spacing_point: layout.line_spacing_point(),
+            ..line_layout.clone()
+        };
+
+        self.new_page();
+        if layout.pagination.chapter_start == ChapterStart::Recto
+            && self.pages.len().is_multiple_of(2)
+        {
+            self.new_page();
+        }
+        self.cursor = self.content_height() / 3.0;
+        for (index, line) in lines.iter().enumerate() {
+            match index + 1 == lines.len() {
+                true => self.write_paragraph(line, &heading_layout)?,
+                false => self.write_paragraph(line, &line_layout)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lists the notes that are yet to be listed, each as a paragraph that starts with its
+    /// number.
+    fn write_notes(&mut self) -> Result<(), BuilderError> {
+        let notes = self.notes.take();
+        if notes.is_empty() {
+            return Ok(());
+        }
+
+        let layout = self.builder.layout.paragraph_layout();
+        if self.builder.layout.notes == NotePlacement::Endnotes {
+            self.write_heading(&[ManuscriptBuilderLayout::DEFAULT_NOTES_TITLE])?;
+        } else {
+            self.cursor += layout.line_spacing_point;
+        }
+        for (number, note) in notes {
+            let mut spans = vec![Span::Text(format!("{}. ", number))];
+            spans.extend(
+                Span::parse(&note)
+                    .into_iter()
+                    .filter(|span| !matches!(span, Span::Note(_))),
+            );
+            self.write_spans(&spans, &layout)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_block(&mut self, block: &Block) -> Result<(), BuilderError> {
+        let layout = &self.builder.layout;
+
+        match block {
+            Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {
+                if layout.notes == NotePlacement::Footnotes {
+                    self.write_notes()?;
+                }
+                self.write_heading(&block.heading_lines())
+            }
+            Block::Paragraph(text) => {
+                let paragraph_layout = ParagraphLayout {
+                    first_line_indention_inch: layout.first_line_indention_inch,
+                    ..layout.paragraph_layout()
+                };
+                let spans = self.paragraph_spans(text);
+                self.write_spans(&spans, &paragraph_layout)
+            }
+            Block::FirstParagraph(text) => {
+                let spans = self.paragraph_spans(text);
+                self.write_spans(&spans, &layout.paragraph_layout())
+            }
+            Block::BlockQuote(text) => {
+                let spans = self.paragraph_spans(text);
+                self.write_spans(&spans, &layout.block_quote_layout())
+            }
+            Block::CenteredParagraph(text) => {
+                let centered_layout = ParagraphLayout {
+                    alignment: AlignmentType::Center,
+                    ..layout.paragraph_layout()
+                };
+                let spans = self.paragraph_spans(text);
+                self.write_spans(&spans, &centered_layout)
+            }
+            Block::Image(image) => {
+                if let Some(placeholder) = ManuscriptBuilder::image_placeholder(image) {
+                    let image_layout = ParagraphLayout {
+                        alignment: image.alignment.into(),
+                        ..layout.paragraph_layout()
+                    };
+                    self.write_paragraph(&placeholder, &image_layout)?;
+                }
+                Ok(())
+            }
+            Block::SceneBreak => {
+                let scene_break_layout = ParagraphLayout {
+                    alignment: AlignmentType::Center,
+                    ..layout.paragraph_layout()
+                };
+                if layout.pagination.keep_with_next {
+                    let next_lines = match layout.pagination.widow_control {
+                        true => 2,
+                        false => 1,
+                    };
+                    self.keep_lines(1 + next_lines, scene_break_layout.line_spacing_point);
+                }
+                self.write_paragraph(&layout.scene_break, &scene_break_layout)
+            }
+        }
+    }
+
+    fn running_head_text(
+        &self,
+        running_head: &RunningHeadLayout,
+        page_number: usize,
+        page_count: usize,
+    ) -> Result<Vec<u16>, BuilderError> {
+        let text: String = running_head
+            .segments(&self.builder.layout)
+            .into_iter()
+            .map(|segment| match segment {
+                RunningHeadSegment::Text(text) => text,
+                RunningHeadSegment::PageNumber => page_number.to_string(),
+                RunningHeadSegment::PageCount => page_count.to_string(),
+            })
+            .collect();
+
+        self.font.encode(&text, false)
+    }
+
+    fn position_running_head(
+        &self,
+        text: Vec<u16>,
+        alignment: AlignmentType,
+        y: f32,
+    ) -> PositionedText {
+        let margin_left = self.builder.layout.page.margin_left_inch * POINTS_PER_INCH;
+        let size = self.builder.layout.font_size_point;
+        let glyphs: Vec<Glyph> = text
+            .iter()
+            .map(|code| Glyph {
+                code: *code,
+                italic: false,
+            })
+            .collect();
+        let width = self.text_width(&glyphs, size);
+        let x = match alignment {
+            AlignmentType::Center => margin_left + (self.content_width() - width) / 2.0,
+            AlignmentType::Right => margin_left + self.content_width() - width,
+            _ => margin_left,
+        };
+
+        PositionedText {
+            x,
+            y,
+            size,
+            text,
+            italic: false,
+        }
+    }
+
+    /// Places the running header and footer halfway into the top and bottom margins.
+    fn write_running_heads(&mut self) -> Result<(), BuilderError> {
+        let page = &self.builder.layout.page;
+        let size = self.builder.layout.font_size_point;
+        let header_y = self.page_height() - page.margin_top_inch * POINTS_PER_INCH / 2.0 - size;
+        let footer_y = page.margin_bottom_inch * POINTS_PER_INCH / 2.0;
+        let page_count = self.pages.len();
+
+        let running_heads = [
+            (self.builder.layout.header.as_ref(), header_y),
+            (self.builder.layout.footer.as_ref(), footer_y),
+        ];
+
+        for (running_head, y) in running_heads {
+            let Some(running_head) = running_head else {
+                continue;
+            };
+
+            for page_index in 0..page_count {
+                if page_index == 0 && !running_head.first_page {
+                    continue;
+                }
+
+                let text = self.running_head_text(running_head, page_index + 1, page_count)?;
+                let positioned_text = self.position_running_head(text, running_head.alignment, y);
+                self.pages[page_index].texts.push(positioned_text);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes laid out pages into the bytes of a PDF file.
+struct PdfWriter {
+    buffer: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    const CATALOG_ID: usize = 1;
+    const PAGES_ID: usize = 2;
+    const FONT_ID: usize = 3;
+    const INFO_ID: usize = 4;
+    const ITALIC_FONT_ID: usize = 5;
+    const FIRST_PAGE_ID: usize = 6;
+
+    fn new() -> Self {
+        Self {
+            buffer: b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec(),
+            offsets: vec![],
+        }
+    }
+
+    fn push_object(&mut self, id: usize, body: &[u8]) {
+        if self.offsets.len() < id {
+            self.offsets.resize(id, 0);
+        }
+        self.offsets[id - 1] = self.buffer.len();
+        self.buffer
+            .extend_from_slice(format!("{} 0 obj\n", id).as_bytes());
+        self.buffer.extend_from_slice(body);
+        self.buffer.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn page_content(page: &PdfPage, font: &PdfFont) -> Vec<u8> {
+        page.texts
+            .iter()
+            .map(|text| {
+                format!(
+                    "BT /{} {:.2} Tf {:.2} {:.2} Td {} Tj ET\n",
+                    match text.italic {
+                        true => "F2",
+                        false => "F1",
+                    },
+                    text.size,
+                    text.x,
+                    text.y,
+                    font.string(&text.text)
+                )
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn push_stream(&mut self, id: usize, dictionary: &str, content: &[u8]) {
+        self.push_object(
+            id,
+            &[
+                format!("<< {} /Length {} >>\nstream\n", dictionary, content.len()).as_bytes(),
+                content,
+                b"\nendstream",
+            ]
+            .concat(),
+        );
+    }
+
+    /// Embeds the used glyphs of a bundled face as a composite font, whose codes are the ids of
+    /// its glyphs. The font and its descendants take up `id` and the four ids from `first_id`.
+    fn push_embedded_font(
+        &mut self,
+        id: usize,
+        first_id: usize,
+        face: &EmbeddedFace,
+        glyph_ids: &BTreeSet<u16>,
+    ) -> Result<(), BuilderError> {
+        let [cid_font_id, descriptor_id, font_file_id, to_unicode_id] =
+            [0, 1, 2, 3].map(|offset| first_id + offset);
+        // A subset font's name is prefixed with a tag of six uppercase letters.
+        let tag: String = (0..6)
+            .map(|index| {
+                let seed = glyph_ids.iter().fold(index as u32, |seed, glyph_id| {
+                    seed.wrapping_mul(31).wrapping_add(*glyph_id as u32)
+                });
+                char::from(b'A' + (seed % 26) as u8)
+            })
+            .collect();
+        let base_font = format!("{}+{}", tag, face.name);
+
+        self.push_object(
+            id,
+            format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{} /Encoding /Identity-H \
+                 /DescendantFonts [{} 0 R] /ToUnicode {} 0 R >>",
+                base_font, cid_font_id, to_unicode_id
+            )
+            .as_bytes(),
+        );
+
+        let widths: Vec<String> = glyph_ids
+            .iter()
+            .map(|glyph_id| format!("{} [{}]", glyph_id, face.width(*glyph_id)))
+            .collect();
+        self.push_object(
+            cid_font_id,
+            format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor {} 0 R /W [{}] /CIDToGIDMap /Identity >>",
+                base_font,
+                descriptor_id,
+                widths.join(" ")
+            )
+            .as_bytes(),
+        );
+
+        let bounding_box = face.face.global_bounding_box();
+        let italic_angle = face.face.italic_angle();
+        self.push_object(
+            descriptor_id,
+            format!(
+                "<< /Type /FontDescriptor /FontName /{} /Flags {} /FontBBox [{} {} {} {}] \
+                 /ItalicAngle {:.2} /Ascent {} /Descent {} /CapHeight {} /StemV 80 \
+                 /FontFile2 {} 0 R >>",
+                base_font,
+                // Serif and nonsymbolic, and italic for a slanted face.
+                match italic_angle != 0.0 {
+                    true => 2 | 32 | 64,
+                    false => 2 | 32,
+                },
+                face.scale(bounding_box.x_min as f32),
+                face.scale(bounding_box.y_min as f32),
+                face.scale(bounding_box.x_max as f32),
+                face.scale(bounding_box.y_max as f32),
+                italic_angle,
+                face.scale(face.face.ascender() as f32),
+                face.scale(face.face.descender() as f32),
+                face.scale(face.face.capital_height().unwrap_or_default() as f32),
+                font_file_id
+            )
+            .as_bytes(),
+        );
+
+        let glyph_ids: Vec<u16> = glyph_ids.iter().copied().collect();
+        let font_file = subsetter::subset(face.data, 0, Profile::pdf(&glyph_ids))?;
+        self.push_stream(
+            font_file_id,
+            &format!("/Length1 {}", font_file.len()),
+            &font_file,
+        );
+
+        let characters: Vec<String> = face
+            .characters(&glyph_ids.into_iter().collect())
+            .into_iter()
+            .map(|(glyph_id, character)| {
+                let mut utf16 = [0; 2];
+                format!(
+                    "<{:04X}> {}",
+                    glyph_id,
+                    hex_string(character.encode_utf16(&mut utf16))
+                )
+            })
+            .collect();
+        let mut to_unicode = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        for chunk in characters.chunks(100) {
+            to_unicode.push_str(&format!(
+                "{} beginbfchar\n{}\nendbfchar\n",
+                chunk.len(),
+                chunk.join("\n")
+            ));
+        }
+        to_unicode
+            .push_str("endcmap\nCMapName currentdict /CMapResource defineresource pop\nend\nend");
+        self.push_stream(to_unicode_id, "", to_unicode.as_bytes());
+
+        Ok(())
+    }
+
+    fn write(
+        mut self,
+        pages: &[PdfPage],
+        font: &PdfFont,
+        page_size: (f32, f32),
+        info: (&str, &str),
+    ) -> Result<Vec<u8>, BuilderError> {
+        let page_ids: Vec<usize> = (0..pages.len())
+            .map(|index| Self::FIRST_PAGE_ID + index * 2)
+            .collect();
+        let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+
+        self.push_object(
+            Self::CATALOG_ID,
+            format!("<< /Type /Catalog /Pages {} 0 R >>", Self::PAGES_ID).as_bytes(),
+        );
+        self.push_object(
+            Self::PAGES_ID,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                pages.len()
+            )
+            .as_bytes(),
+        );
+        match font {
+            PdfFont::Standard(font) => {
+                for (id, base_font) in [
+                    (Self::FONT_ID, font.base_font()),
+                    (Self::ITALIC_FONT_ID, font.italic_base_font()),
+                ] {
+                    self.push_object(
+                        id,
+                        format!(
+                            "<< /Type /Font /Subtype /Type1 /BaseFont /{} \
+                             /Encoding /WinAnsiEncoding >>",
+                            base_font
+                        )
+                        .as_bytes(),
+                    );
+                }
+            }
+            PdfFont::Embedded(faces) => {
+                let mut next_id = Self::FIRST_PAGE_ID + pages.len() * 2;
+                for (id, face, is_italic) in [
+                    (Self::FONT_ID, &faces.0, false),
+                    (Self::ITALIC_FONT_ID, &faces.1, true),
+                ] {
+                    let glyph_ids: BTreeSet<u16> = pages
+                        .iter()
+                        .flat_map(|page| &page.texts)
+                        .filter(|text| text.italic == is_italic)
+                        .flat_map(|text| text.text.iter().copied())
+                        .collect();
+                    self.push_embedded_font(id, next_id, face, &glyph_ids)?;
+                    next_id += 4;
+                }
+            }
+        }
+        self.push_object(
+            Self::INFO_ID,
+            format!(
+                "<< /Title {} /Author {} /Producer (Makinilya) >>",
+                text_string(info.0),
+                text_string(info.1)
+            )
+            .as_bytes(),
+        );
+
+        for (page, page_id) in pages.iter().zip(page_ids) {
+            let content = Self::page_content(page, font);
+
+            self.push_object(
+                page_id,
+                format!(
+                    "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {:.2} {:.2}] \
+                     /Resources << /Font << /F1 {} 0 R /F2 {} 0 R >> >> /Contents {} 0 R >>",
+                    Self::PAGES_ID,
+                    page_size.0,
+                    page_size.1,
+                    Self::FONT_ID,
+                    Self::ITALIC_FONT_ID,
+                    page_id + 1
+                )
+                .as_bytes(),
+            );
+            self.push_object(
+                page_id + 1,
+                &[
+                    format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(),
+                    &content,
+                    b"endstream",
+                ]
+                .concat(),
+            );
+        }
+
+        let xref_offset = self.buffer.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            xref.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        xref.push_str(&format!(
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.offsets.len() + 1,
+            Self::CATALOG_ID,
+            Self::INFO_ID,
+            xref_offset
+        ));
+        self.buffer.extend_from_slice(xref.as_bytes());
+
+        Ok(self.buffer)
+    }
+}
+
+impl ManuscriptBuilder {
+    fn layout_pdf(
+        &self,
+        document: &ManuscriptDocument,
+        font: PdfFont,
+    ) -> Result<PdfLayouter<'_>, BuilderError> {
+        let mut layouter = PdfLayouter::new(self, font);
+        layouter.write_title_page(WordCount::count_document(document))?;
+        for block in self.paged_blocks(document) {
+            layouter.write_block(block)?;
+        }
+        layouter.write_notes()?;
+        layouter.write_running_heads()?;
+
+        Ok(layouter)
+    }
+
+    /// Builds the PDF in the standard font of the layout's family, or in the bundled font when
+    /// the manuscript has a character that the standard fonts can't encode.
+    pub fn build_pdf(&self, story: &Story) -> Result<Vec<u8>, BuilderError> {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let font = PdfFont::Standard(StandardFont::from_family(&self.layout.font_family));
+        let layouter = match self.layout_pdf(&document, font) {
+            Err(BuilderError::UnencodableCharacter(_)) => {
+                self.layout_pdf(&document, PdfFont::embedded())?
+            }
+            layouter => layouter?,
+        };
+
+        let page_size = (
+            self.layout.page.width_inch * POINTS_PER_INCH,
+            self.layout.page.height_inch * POINTS_PER_INCH,
+        );
+
+        PdfWriter::new().write(
+            &layouter.pages,
+            &layouter.font,
+            page_size,
+            (&self.layout.title, &self.layout.pen_name),
+        )
+    }
+}
+
+#[cfg(test)]
+mod pdf_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn maps_standard_fonts() {
+        assert_eq!(
+            StandardFont::from_family("Times New Roman"),
+            StandardFont::Times
+        );
+        assert_eq!(
+            StandardFont::from_family("Courier New"),
+            StandardFont::Courier
+        );
+        assert_eq!(StandardFont::from_family("Arial"), StandardFont::Helvetica);
+    }
+
+    #[test]
+    fn escapes_literal_strings() {
+        assert_eq!(literal_string(&encode("(a\\b)").unwrap()), "(\\(a\\\\b\\))");
+        assert_eq!(literal_string(&encode("“Hi”").unwrap()), "(\\223Hi\\224)");
+        assert_eq!(
+            encode("Oui\u{202f}! Non\u{2011}stop").unwrap(),
+            b"Oui\xa0! Non-stop"
+        );
+        assert!(matches!(
+            encode("Καλημέρα"),
+            Err(BuilderError::UnencodableCharacter('Κ'))
+        ));
+    }
+
+    #[test]
+    fn builds_pdf() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I am Scene #1. ".repeat(400));
+        chapter_1.push_content("I am Scene #2.");
+        chapter_1.push_content("I have *never* been.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let pdf = builder.build_pdf(&story).unwrap();
+        let source = String::from_utf8_lossy(&pdf);
+
+        assert!(source.starts_with("%PDF-1.4"));
+        assert!(source.ends_with("%%EOF\n"));
+        assert!(source.contains("/BaseFont /Times-Roman"));
+        assert!(source.contains("/BaseFont /Times-Italic"));
+        assert!(source.contains("(I am Scene #2.)"));
+        assert!(source
+            .lines()
+            .any(|line| line.starts_with("BT /F2 ") && line.ends_with("(never) Tj ET")));
+        assert!(source.matches("/Type /Page ").count() > 2);
+    }
+
+    #[test]
+    fn embeds_bundled_font() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Глава 1");
+        chapter_1.push_content("Καλημέρα, *мир*.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let pdf = builder.build_pdf(&story).unwrap();
+        let source = String::from_utf8_lossy(&pdf);
+
+        assert!(!source.contains("/BaseFont /Times-Roman"));
+        assert!(source.contains("+DejaVuSerif /Encoding /Identity-H"));
+        assert!(source.contains("+DejaVuSerif-Italic /Encoding /Identity-H"));
+        assert_eq!(source.matches("/FontFile2 ").count(), 2);
+        assert_eq!(source.matches("/CMapName /Adobe-Identity-UCS").count(), 2);
+
+        let font = PdfFont::embedded();
+        let kappa = font.encode("Κ", false).unwrap()[0];
+        assert!(source.contains(&format!("<{:04X}> <039A>", kappa)));
+
+        story.push_content("東京");
+        assert!(matches!(
+            builder.build_pdf(&story),
+            Err(BuilderError::UnencodableCharacter('東'))
+        ));
+    }
+
+    #[test]
+    fn controls_widows_and_orphans() {
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let mut layouter = PdfLayouter::new(&builder, PdfFont::Standard(StandardFont::Times));
+        let line_spacing_point = builder.layout.line_spacing_point();
+
+        assert_eq!(layouter.widow_break(3, line_spacing_point), None);
+        layouter.cursor = layouter.content_height() - line_spacing_point;
+        assert_eq!(layouter.widow_break(3, line_spacing_point), Some(0));
+        layouter.cursor = layouter.content_height() - 3.0 * line_spacing_point;
+        assert_eq!(layouter.widow_break(3, line_spacing_point), None);
+        assert_eq!(layouter.widow_break(4, line_spacing_point), Some(2));
+        assert_eq!(layouter.widow_break(5, line_spacing_point), None);
+    }
+
+    #[test]
+    fn starts_chapters_on_recto_pages() {
+        let mut story = Story::new("Root");
+        for name in ["Chapter 1", "Chapter 2"] {
+            let mut chapter = Story::new(name);
+            chapter.push_content("I am a scene.");
+            story.push_part(chapter);
+        }
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let pdf = builder.build_pdf(&story).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&pdf)
+                .matches("/Type /Page ")
+                .count(),
+            3
+        );
+
+        let mut layout = ManuscriptBuilderLayout::default();
+        layout.pagination.chapter_start = ChapterStart::Recto;
+        let pdf = ManuscriptBuilder::new(layout).build_pdf(&story).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&pdf)
+                .matches("/Type /Page ")
+                .count(),
+            5
+        );
+    }
+}
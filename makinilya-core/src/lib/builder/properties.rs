@@ -0,0 +1,96 @@
+//! Document properties of the word document.
+//!
+//! `docx_rs` only sets the timestamps of the core properties, so the core and extended
+//! properties are written from the layout instead. The version of makinilya that built the
+//! document is set as the custom `makinilya version` property.
+
+use docx_rs::Docx;
+
+use super::ManuscriptBuilder;
+use crate::extensions::EscapeXml;
+
+pub const VERSION_PROPERTY: &str = "makinilya version";
+
+impl ManuscriptBuilder {
+    /// Sets the custom properties of the document, which `docx_rs` registers with the package.
+    pub(crate) fn build_docx_custom_properties(&self, doc: Docx) -> Docx {
+        doc.custom_property(VERSION_PROPERTY, env!("CARGO_PKG_VERSION"))
+    }
+
+    /// Builds the `core.xml` of the document, whose creation and modification times are both
+    /// the `timestamp` of the build.
+    pub fn build_docx_core_properties(&self, timestamp: &str) -> Vec<u8> {
+        let layout = &self.layout;
+        let mut properties = format!(
+            "<dc:title>{}</dc:title><dc:creator>{}</dc:creator><cp:lastModifiedBy>{}</cp:lastModifiedBy>",
+            layout.title.escape_xml(),
+            layout.pen_name.escape_xml(),
+            layout.pen_name.escape_xml()
+        );
+        if let Some(subject) = &layout.subject {
+            properties.push_str(&format!(
+                "<dc:subject>{}</dc:subject>",
+                subject.escape_xml()
+            ));
+        }
+        if !layout.keywords.is_empty() {
+            properties.push_str(&format!(
+                "<cp:keywords>{}</cp:keywords>",
+                layout.keywords.join(", ").escape_xml()
+            ));
+        }
+        properties.push_str(&format!(
+            r#"<dc:language>{0}</dc:language><cp:revision>1</cp:revision><dcterms:created xsi:type="dcterms:W3CDTF">{1}</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">{1}</dcterms:modified>"#,
+            layout.language.escape_xml(),
+            timestamp
+        ));
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:dcmitype="http://purl.org/dc/dcmitype/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">{}</cp:coreProperties>"#,
+            properties
+        )
+        .into_bytes()
+    }
+
+    /// Builds the `app.xml` of the document, which carries the word count of the story.
+    pub fn build_docx_app_properties(&self, word_count: u32) -> Vec<u8> {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><Application>Makinilya</Application><Words>{}</Words></Properties>"#,
+            word_count
+        )
+        .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod properties_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn builds_docx_properties() {
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout {
+            title: "Tom & Jerry".into(),
+            subject: Some("Fiction".into()),
+            keywords: vec!["cats".into(), "mice".into()],
+            ..Default::default()
+        });
+
+        let core =
+            String::from_utf8(builder.build_docx_core_properties("2024-01-02T03:04:05Z")).unwrap();
+        assert!(core.contains(
+            "<dc:title>Tom &amp; Jerry</dc:title><dc:creator>Unknown Author</dc:creator>"
+        ));
+        assert!(
+            core.contains("<dc:subject>Fiction</dc:subject><cp:keywords>cats, mice</cp:keywords>")
+        );
+        assert!(core.contains(
+            r#"<dcterms:modified xsi:type="dcterms:W3CDTF">2024-01-02T03:04:05Z</dcterms:modified>"#
+        ));
+
+        let app = String::from_utf8(builder.build_docx_app_properties(1237)).unwrap();
+        assert!(app.contains("<Words>1237</Words>"));
+    }
+}
@@ -0,0 +1,344 @@
+//! Rich Text Format (RTF) output of the manuscript.
+//!
+//! The document mirrors the word document: a title page with the contact information and the
+//! word count, chapters that start on a new page a third of the way down, and running heads with
+//! page number fields. Line spacing is exact, so the title page could be laid out with the space
+//! before its paragraphs instead of a table. Notes are written as RTF footnotes, which are
+//! marked as endnotes when the layout places them at the end.
+
+use docx_rs::AlignmentType;
+
+use super::{
+    document::{Block, ManuscriptDocument, Span},
+    ManuscriptBuilder, ParagraphLayout, RunningHeadLayout, RunningHeadSegment,
+};
+use crate::{config::NotePlacement, story::Story, word_count::WordCount};
+
+const TWIPS_PER_POINT: f32 = 20.0;
+const TWIPS_PER_INCH: f32 = 1440.0;
+
+/// Escapes text into RTF. Characters outside of ASCII are written as `\uN?` escapes of their
+/// UTF-16 code units, with a question mark for readers that do not support Unicode.
+fn escape_rtf(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        match character {
+            '\\' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\t' => escaped.push_str("\\tab "),
+            ' '..='~' => escaped.push(character),
+            _ if character.is_control() => (),
+            _ => {
+                let mut units = [0u16; 2];
+                for unit in character.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{}?", *unit as i16));
+                }
+            }
+        }
+    }
+
+    escaped
+}
+
+fn twips(points: f32) -> i32 {
+    (points * TWIPS_PER_POINT).round() as i32
+}
+
+impl ManuscriptBuilder {
+    fn rtf_paragraph(&self, content: &str, layout: &ParagraphLayout, properties: &str) -> String {
+        let alignment = match layout.alignment {
+            AlignmentType::Center => "\\qc",
+            AlignmentType::Right => "\\qr",
+            AlignmentType::Both => "\\qj",
+            _ => "\\ql",
+        };
+
+        let widow_control = match self.layout.pagination.widow_control {
+            true => "\\widctlpar",
+            false => "\\nowidctlpar",
+        };
+
+        format!(
+            "\\pard\\plain{}{}{}\\fi{}\\li{}\\ri{}\\sl-{}\\slmult0\\sa{}\\f0\\fs{} {}\\par\n",
+            widow_control,
+            properties,
+            alignment,
+            (layout.first_line_indention_inch * TWIPS_PER_INCH).round() as i32,
+            (layout.left_indention_inch * TWIPS_PER_INCH).round() as i32,
+            (layout.right_indention_inch * TWIPS_PER_INCH).round() as i32,
+            twips(layout.line_spacing_point),
+            twips(layout.after_line_spacing_point),
+            (layout.font_size_point * 2.0).round() as i32,
+            content
+        )
+    }
+
+    /// Escapes the text of a paragraph, with its emphasis in italics and its notes as
+    /// automatically numbered footnotes or endnotes.
+    fn rtf_inline(&self, text: &str) -> String {
+        Span::parse(text)
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) => escape_rtf(text),
+                Span::Emphasis(text) => format!("{{\\i {}}}", escape_rtf(text)),
+                Span::Note(note) => {
+                    let layout = ParagraphLayout {
+                        line_spacing_point: self.layout.font_size_point,
+                        ..self.layout.paragraph_layout()
+                    };
+                    let content = format!("{{\\super\\chftn}} {}", self.rtf_inline(note));
+                    format!(
+                        "{{\\super\\chftn}}{{\\footnote{} {}}}",
+                        match self.layout.notes {
+                            NotePlacement::Footnotes => "",
+                            NotePlacement::Endnotes => "\\ftnalt",
+                        },
+                        self.rtf_paragraph(&content, &layout, "").trim_end()
+                    )
+                }
+            })
+            .collect()
+    }
+
+    fn rtf_running_head(&self, destination: &str, running_head: &RunningHeadLayout) -> String {
+        let content: String = running_head
+            .segments(&self.layout)
+            .iter()
+            .map(|segment| match segment {
+                RunningHeadSegment::Text(text) => escape_rtf(text),
+                RunningHeadSegment::PageNumber => "\\chpgn ".to_string(),
+                RunningHeadSegment::PageCount => {
+                    "{\\field{\\*\\fldinst NUMPAGES}{\\fldrslt 1}}".to_string()
+                }
+            })
+            .collect();
+        let layout = ParagraphLayout {
+            line_spacing_point: self.layout.font_size_point,
+            alignment: running_head.alignment,
+            ..self.layout.paragraph_layout()
+        };
+
+        format!(
+            "{{\\{} {}}}\n",
+            destination,
+            self.rtf_paragraph(&content, &layout, "").trim_end()
+        )
+    }
+
+    fn rtf_running_heads(&self) -> String {
+        let running_heads = [
+            ("header", self.layout.header.as_ref()),
+            ("footer", self.layout.footer.as_ref()),
+        ];
+        let title_page = running_heads
+            .iter()
+            .any(|(_, running_head)| running_head.is_some_and(|head| !head.first_page));
+        let mut rtf = String::new();
+
+        if title_page {
+            rtf.push_str("\\titlepg\n");
+        }
+
+        for (destination, running_head) in running_heads {
+            if let Some(running_head) = running_head {
+                rtf.push_str(&self.rtf_running_head(destination, running_head));
+                if title_page && running_head.first_page {
+                    rtf.push_str(
+                        &self.rtf_running_head(&format!("{}f", destination), running_head),
+                    );
+                }
+            }
+        }
+
+        rtf
+    }
+
+    fn rtf_title_page(&self, word_count: u32) -> String {
+        let title_page = self.layout.title_page_layout(word_count);
+        let mut rtf = String::new();
+
+        let blocks = [
+            (
+                &title_page.middle_lines,
+                &title_page.middle_layout,
+                title_page.middle_space_before_point,
+            ),
+            (
+                &title_page.bottom_lines,
+                &title_page.bottom_layout,
+                title_page.bottom_space_before_point,
+            ),
+        ];
+
+        for (line, right_line) in title_page.top_rows() {
+            rtf.push_str(&match right_line {
+                Some(right_line) => self.rtf_paragraph(
+                    &format!("{}\\tab {}", escape_rtf(line), escape_rtf(right_line)),
+                    &title_page.top_layout,
+                    &format!(
+                        "\\tqr\\tx{}",
+                        (self.layout.page.content_width_inch() * TWIPS_PER_INCH).round() as i32
+                    ),
+                ),
+                None => self.rtf_paragraph(&escape_rtf(line), &title_page.top_layout, ""),
+            });
+        }
+
+        for (lines, layout, space_before) in blocks {
+            for (index, line) in lines.iter().enumerate() {
+                let properties = match index {
+                    0 => format!("\\sb{}", twips(space_before)),
+                    _ => String::new(),
+                };
+                rtf.push_str(&self.rtf_paragraph(&escape_rtf(line), layout, &properties));
+            }
+        }
+
+        rtf
+    }
+
+    fn rtf_block(&self, block: &Block) -> String {
+        let layout = &self.layout;
+
+        match block {
+            Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {
+                let title = block
+                    .heading_lines()
+                    .iter()
+                    .map(|line| escape_rtf(line))
+                    .collect::<Vec<String>>()
+                    .join("\\line ");
+                let third_height = layout.page.content_height_inch() * 72.0 / 3.0;
+                self.rtf_paragraph(
+                    &title,
+                    &ParagraphLayout {
+                        after_line_spacing_point: layout.line_spacing_point(),
+                        alignment: AlignmentType::Center,
+                        ..layout.paragraph_layout()
+                    },
+                    &format!(
+                        "\\pagebb{}\\sb{}",
+                        self.rtf_keep_with_next(),
+                        twips(third_height)
+                    ),
+                )
+            }
+            Block::Paragraph(text)
+            | Block::FirstParagraph(text)
+            | Block::CenteredParagraph(text)
+            | Block::BlockQuote(text) => {
+                let content = self.rtf_inline(text);
+                let paragraph_layout = match block {
+                    Block::CenteredParagraph(_) => ParagraphLayout {
+                        alignment: AlignmentType::Center,
+                        ..layout.paragraph_layout()
+                    },
+                    Block::FirstParagraph(_) => layout.paragraph_layout(),
+                    Block::BlockQuote(_) => layout.block_quote_layout(),
+                    _ => ParagraphLayout {
+                        first_line_indention_inch: layout.first_line_indention_inch,
+                        ..layout.paragraph_layout()
+                    },
+                };
+                self.rtf_paragraph(&content, &paragraph_layout, "")
+            }
+            Block::Image(image) => match Self::image_placeholder(image) {
+                Some(placeholder) => self.rtf_paragraph(
+                    &escape_rtf(&placeholder),
+                    &ParagraphLayout {
+                        alignment: image.alignment.into(),
+                        ..layout.paragraph_layout()
+                    },
+                    "",
+                ),
+                None => String::new(),
+            },
+            Block::SceneBreak => self.rtf_paragraph(
+                &escape_rtf(&layout.scene_break),
+                &ParagraphLayout {
+                    alignment: AlignmentType::Center,
+                    ..layout.paragraph_layout()
+                },
+                self.rtf_keep_with_next(),
+            ),
+        }
+    }
+
+    /// The property of a heading or a scene break that keeps it with the next paragraph.
+    fn rtf_keep_with_next(&self) -> &'static str {
+        match self.layout.pagination.keep_with_next {
+            true => "\\keepn",
+            false => "",
+        }
+    }
+
+    pub fn build_rtf(&self, story: &Story) -> String {
+        let page = &self.layout.page;
+        let inch = |inches: f32| (inches * TWIPS_PER_INCH).round() as i32;
+
+        let mut rtf = format!(
+            "{{\\rtf1\\ansi\\ansicpg1252\\deff0\\uc1\n{{\\fonttbl{{\\f0\\fnil\\fcharset0 {};}}}}\n{{\\info{{\\title {}}}{{\\author {}}}}}\n",
+            escape_rtf(&self.layout.font_family),
+            escape_rtf(&self.layout.title),
+            escape_rtf(&self.layout.pen_name)
+        );
+        rtf.push_str(&format!(
+            "\\paperw{}\\paperh{}\\margl{}\\margr{}\\margt{}\\margb{}\n\\sectd\\headery{}\\footery{}\n",
+            inch(page.width_inch),
+            inch(page.height_inch),
+            inch(page.margin_left_inch),
+            inch(page.margin_right_inch),
+            inch(page.margin_top_inch),
+            inch(page.margin_bottom_inch),
+            inch(page.margin_top_inch / 2.0),
+            inch(page.margin_bottom_inch / 2.0)
+        ));
+        if self.layout.notes == NotePlacement::Endnotes {
+            rtf.push_str("\\fet1\\aenddoc\n");
+        }
+        rtf.push_str(&self.rtf_running_heads());
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        rtf.push_str(&self.rtf_title_page(WordCount::count_document(&document)));
+
+        for block in self.paged_blocks(&document) {
+            rtf.push_str(&self.rtf_block(block));
+        }
+
+        rtf.push_str("}\n");
+        rtf
+    }
+}
+
+#[cfg(test)]
+mod rtf_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn escapes_rtf() {
+        assert_eq!(escape_rtf("{a}\\b"), "\\{a\\}\\\\b");
+        assert_eq!(escape_rtf("café 😀"), "caf\\u233? \\u-10179?\\u-8704?");
+    }
+
+    #[test]
+    fn builds_rtf() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I have *never*^[Not *once*.] been to the sea.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+        let rtf = builder.build_rtf(&story);
+
+        assert!(rtf.starts_with("{\\rtf1"));
+        assert!(rtf.ends_with("}\n"));
+        assert!(rtf.contains("I have {\\i never}{\\super\\chftn}{\\footnote \\pard"));
+        assert!(rtf.contains("{\\super\\chftn} Not {\\i once}.\\par} been to the sea.\\par"));
+        assert!(rtf.contains("\\titlepg"));
+        assert!(rtf.contains("Unknown Author\\par"));
+        assert!(rtf.contains("\\pard\\plain\\widctlpar\\pagebb\\keepn\\sb"));
+    }
+}
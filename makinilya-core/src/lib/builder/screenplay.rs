@@ -0,0 +1,411 @@
+//! Screenplay output of the interpolated story.
+//!
+//! Every scene is parsed from the Fountain conventions and laid out in the industry format:
+//! Courier 12 with six lines to an inch, on a one and a half inch left margin. Since every
+//! character has the same width, the pages are broken by the builder itself, which keeps a scene
+//! heading with the element after it and never splits a block of dialogue. Parts and chapters
+//! only group the scenes and are not presented.
+
+use docx_rs::{
+    AlignmentType, BreakType, Docx, FieldCharType, Header, InstrPAGE, InstrText, LineSpacing,
+    LineSpacingType, PageMargin, PageNumType, Paragraph, Run, RunFonts,
+};
+
+use super::{document::Span, BuilderError, ManuscriptBuilder};
+use crate::{
+    fountain::{Fountain, ScreenplayElement},
+    story::Story,
+    units::{HalfPoint, Twip},
+};
+
+/// Position of an element between the margins of the page, in inches from the left margin.
+#[derive(Debug, Clone, Copy)]
+struct ElementLayout {
+    indent_inch: f32,
+    width_inch: f32,
+    alignment: AlignmentType,
+    blank_lines_before: usize,
+}
+
+impl ManuscriptBuilder {
+    const SCREENPLAY_FONT_FAMILY: &'static str = "Courier New";
+    const SCREENPLAY_FONT_SIZE_POINT: f32 = 12.0;
+    const SCREENPLAY_CHARACTERS_PER_INCH: f32 = 10.0;
+    const SCREENPLAY_MARGIN_LEFT_INCH: f32 = 1.5;
+    const SCREENPLAY_MARGIN_INCH: f32 = 1.0;
+
+    fn screenplay_content_width_inch(&self) -> f32 {
+        self.layout.page.width_inch
+            - Self::SCREENPLAY_MARGIN_LEFT_INCH
+            - Self::SCREENPLAY_MARGIN_INCH
+    }
+
+    fn screenplay_lines_per_page(&self) -> usize {
+        let content_height_inch = self.layout.page.height_inch - Self::SCREENPLAY_MARGIN_INCH * 2.0;
+        (content_height_inch * 72.0 / Self::SCREENPLAY_FONT_SIZE_POINT) as usize
+    }
+
+    fn element_layout(&self, element: &ScreenplayElement) -> ElementLayout {
+        let full_width = ElementLayout {
+            indent_inch: 0.0,
+            width_inch: self.screenplay_content_width_inch(),
+            alignment: AlignmentType::Left,
+            blank_lines_before: 1,
+        };
+
+        match element {
+            ScreenplayElement::Character(_) => ElementLayout {
+                indent_inch: 2.2,
+                width_inch: full_width.width_inch - 2.2,
+                ..full_width
+            },
+            ScreenplayElement::Parenthetical(_) => ElementLayout {
+                indent_inch: 1.6,
+                width_inch: 2.0,
+                blank_lines_before: 0,
+                ..full_width
+            },
+            ScreenplayElement::Dialogue(_) => ElementLayout {
+                indent_inch: 1.0,
+                width_inch: 3.5,
+                blank_lines_before: 0,
+                ..full_width
+            },
+            ScreenplayElement::Transition(_) => ElementLayout {
+                alignment: AlignmentType::Right,
+                ..full_width
+            },
+            ScreenplayElement::Centered(_) => ElementLayout {
+                alignment: AlignmentType::Center,
+                ..full_width
+            },
+            _ => full_width,
+        }
+    }
+
+    fn element_text(element: &ScreenplayElement) -> &str {
+        match element {
+            ScreenplayElement::SceneHeading(text)
+            | ScreenplayElement::Action(text)
+            | ScreenplayElement::Character(text)
+            | ScreenplayElement::Parenthetical(text)
+            | ScreenplayElement::Dialogue(text)
+            | ScreenplayElement::Transition(text)
+            | ScreenplayElement::Centered(text) => text,
+            ScreenplayElement::PageBreak => "",
+        }
+    }
+
+    /// Counts the lines that the element takes up once it's wrapped within its width.
+    fn element_line_count(&self, element: &ScreenplayElement) -> usize {
+        let layout = self.element_layout(element);
+        let width = (layout.width_inch * Self::SCREENPLAY_CHARACTERS_PER_INCH) as usize;
+
+        Self::element_text(element)
+            .lines()
+            .map(|line| Self::wrap_text(line, width.max(1)).lines().count().max(1))
+            .sum()
+    }
+
+    fn screenplay_paragraph(
+        &self,
+        text: &str,
+        layout: ElementLayout,
+        blank_lines: usize,
+    ) -> Paragraph {
+        let fonts = RunFonts::new()
+            .ascii(Self::SCREENPLAY_FONT_FAMILY)
+            .hi_ansi(Self::SCREENPLAY_FONT_FAMILY);
+        let size: usize = HalfPoint::from_point(Self::SCREENPLAY_FONT_SIZE_POINT).into();
+        let line_height: u32 = Twip::from_point(Self::SCREENPLAY_FONT_SIZE_POINT).into();
+        let right_indent_inch =
+            (self.screenplay_content_width_inch() - layout.indent_inch - layout.width_inch)
+                .max(0.0);
+        let mut paragraph = Paragraph::new()
+            .align(layout.alignment)
+            .fonts(fonts.clone())
+            .size(size);
+
+        for (index, line) in text.lines().enumerate() {
+            if index > 0 {
+                paragraph = paragraph.add_run(Run::new().add_break(BreakType::TextWrapping));
+            }
+
+            for span in Span::parse(line) {
+                let run = Run::new()
+                    .add_text(span.text())
+                    .fonts(fonts.clone())
+                    .size(size);
+
+                paragraph = match span {
+                    Span::Emphasis(_) => paragraph.add_run(run.italic()),
+                    Span::Text(_) => paragraph.add_run(run),
+                    // Notes are left out of a screenplay.
+                    Span::Note(_) => paragraph,
+                };
+            }
+        }
+
+        paragraph
+            .line_spacing(
+                LineSpacing::new()
+                    .line_rule(LineSpacingType::Exact)
+                    .line(line_height as i32)
+                    .before(line_height * blank_lines as u32),
+            )
+            .indent(
+                Some(Twip::from_inch(layout.indent_inch).into()),
+                None,
+                Some(Twip::from_inch(right_indent_inch).into()),
+                None,
+            )
+    }
+
+    fn screenplay_title_page(&self, mut doc: Docx) -> Docx {
+        let lines_per_page = self.screenplay_lines_per_page();
+        let centered = ElementLayout {
+            indent_inch: 0.0,
+            width_inch: self.screenplay_content_width_inch(),
+            alignment: AlignmentType::Center,
+            blank_lines_before: 0,
+        };
+        let contact_lines: Vec<String> = self
+            .layout
+            .agent_information
+            .as_ref()
+            .or(self.layout.author_information.as_ref())
+            .map_or(vec![], |information| {
+                information.lines().into_iter().cloned().collect()
+            });
+
+        let title_line = lines_per_page / 3;
+        doc = doc
+            .add_paragraph(self.screenplay_paragraph(
+                &self.layout.title.to_uppercase(),
+                centered,
+                title_line,
+            ))
+            .add_paragraph(self.screenplay_paragraph("Written by", centered, 3))
+            .add_paragraph(self.screenplay_paragraph(&self.layout.pen_name, centered, 1));
+
+        let used_lines = title_line + 7;
+        let mut blank_lines = lines_per_page.saturating_sub(used_lines + contact_lines.len());
+
+        for line in &contact_lines {
+            doc = doc.add_paragraph(self.screenplay_paragraph(
+                line,
+                ElementLayout {
+                    alignment: AlignmentType::Left,
+                    ..centered
+                },
+                std::mem::take(&mut blank_lines),
+            ));
+        }
+
+        doc
+    }
+
+    /// The page number on the top right of every page but the title page, e.g. `12.`.
+    fn screenplay_header(&self) -> Header {
+        let fonts = RunFonts::new()
+            .ascii(Self::SCREENPLAY_FONT_FAMILY)
+            .hi_ansi(Self::SCREENPLAY_FONT_FAMILY);
+        let size: usize = HalfPoint::from_point(Self::SCREENPLAY_FONT_SIZE_POINT).into();
+        let runs = vec![
+            Run::new().add_field_char(FieldCharType::Begin, false),
+            Run::new().add_instr_text(InstrText::PAGE(InstrPAGE::new())),
+            Run::new().add_field_char(FieldCharType::Separate, false),
+            Run::new().add_text("1"),
+            Run::new().add_field_char(FieldCharType::End, false),
+            Run::new().add_text("."),
+        ];
+
+        Header::new().add_paragraph(
+            runs.into_iter().fold(
+                Paragraph::new()
+                    .align(AlignmentType::Right)
+                    .fonts(fonts.clone())
+                    .size(size),
+                |paragraph, run| paragraph.add_run(run.fonts(fonts.clone()).size(size)),
+            ),
+        )
+    }
+
+    /// Groups the elements into units that are never split across pages: a block of dialogue,
+    /// or a scene heading together with the element after it.
+    fn screenplay_units(elements: Vec<ScreenplayElement>) -> Vec<Vec<ScreenplayElement>> {
+        let mut units: Vec<Vec<ScreenplayElement>> = vec![];
+
+        for element in elements {
+            let joins_unit = match (units.last(), &element) {
+                (_, ScreenplayElement::PageBreak) => false,
+                (
+                    Some(unit),
+                    ScreenplayElement::Parenthetical(_) | ScreenplayElement::Dialogue(_),
+                ) => unit
+                    .iter()
+                    .any(|element| matches!(element, ScreenplayElement::Character(_))),
+                (Some(unit), _) => matches!(unit.as_slice(), [ScreenplayElement::SceneHeading(_)]),
+                (None, _) => false,
+            };
+
+            match (joins_unit, units.last_mut()) {
+                (true, Some(unit)) => unit.push(element),
+                _ => units.push(vec![element]),
+            }
+        }
+
+        units
+    }
+
+    fn collect_screenplay_elements(story: &Story, elements: &mut Vec<ScreenplayElement>) {
+        for content in story.contents() {
+            elements.append(&mut Fountain::parse(content));
+        }
+
+        for part in story.parts() {
+            Self::collect_screenplay_elements(part, elements);
+        }
+    }
+
+    pub fn build_screenplay(&self, story: &Story) -> Result<Docx, BuilderError> {
+        let page = &self.layout.page;
+        let mut doc = Docx::new()
+            .page_size(
+                Twip::from_inch(page.width_inch).into(),
+                Twip::from_inch(page.height_inch).into(),
+            )
+            .page_margin(
+                PageMargin::new()
+                    .top(Twip::from_inch(Self::SCREENPLAY_MARGIN_INCH).into())
+                    .bottom(Twip::from_inch(Self::SCREENPLAY_MARGIN_INCH).into())
+                    .left(Twip::from_inch(Self::SCREENPLAY_MARGIN_LEFT_INCH).into())
+                    .right(Twip::from_inch(Self::SCREENPLAY_MARGIN_INCH).into())
+                    .header(Twip::from_inch(Self::SCREENPLAY_MARGIN_INCH / 2.0).into()),
+            )
+            .page_num_type(PageNumType::new().start(0))
+            .header(self.screenplay_header())
+            .first_header(Header::new());
+
+        doc = self.screenplay_title_page(doc);
+
+        let mut elements = vec![];
+        Self::collect_screenplay_elements(story, &mut elements);
+
+        let continued = self.layout.screenplay.continued;
+        let available_lines = self.screenplay_lines_per_page() - if continued { 2 } else { 0 };
+        let full_width = self.element_layout(&ScreenplayElement::PageBreak);
+        let mut line = 0;
+        let mut page_break_before = true;
+        let mut in_scene = false;
+
+        for unit in Self::screenplay_units(elements) {
+            if unit.first() == Some(&ScreenplayElement::PageBreak) {
+                line = 0;
+                page_break_before = true;
+                continue;
+            }
+
+            let height: usize = unit
+                .iter()
+                .map(|element| {
+                    self.element_layout(element).blank_lines_before
+                        + self.element_line_count(element)
+                })
+                .sum();
+            let starts_scene = matches!(unit.first(), Some(ScreenplayElement::SceneHeading(_)));
+
+            if line > 0 && line + height > available_lines {
+                line = 0;
+                page_break_before = true;
+
+                if continued && in_scene && !starts_scene {
+                    doc = doc
+                        .add_paragraph(self.screenplay_paragraph(
+                            "(CONTINUED)",
+                            ElementLayout {
+                                alignment: AlignmentType::Right,
+                                ..full_width
+                            },
+                            1,
+                        ))
+                        .add_paragraph(
+                            self.screenplay_paragraph("CONTINUED:", full_width, 0)
+                                .page_break_before(true),
+                        );
+                    line = 1;
+                    page_break_before = false;
+                }
+            }
+
+            for element in &unit {
+                let layout = self.element_layout(element);
+                let blank_lines = match line {
+                    0 => 0,
+                    _ => layout.blank_lines_before,
+                };
+
+                doc = doc.add_paragraph(
+                    self.screenplay_paragraph(Self::element_text(element), layout, blank_lines)
+                        .page_break_before(std::mem::take(&mut page_break_before)),
+                );
+                line += blank_lines + self.element_line_count(element);
+            }
+
+            in_scene |= starts_scene;
+        }
+
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod screenplay_tests {
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn groups_screenplay_units() {
+        let units = ManuscriptBuilder::screenplay_units(vec![
+            ScreenplayElement::SceneHeading("INT. KITCHEN - NIGHT".into()),
+            ScreenplayElement::Character("MARK".into()),
+            ScreenplayElement::Parenthetical("(whispering)".into()),
+            ScreenplayElement::Dialogue("Is anyone there?".into()),
+            ScreenplayElement::Action("No one answers.".into()),
+            ScreenplayElement::PageBreak,
+            ScreenplayElement::Transition("CUT TO:".into()),
+        ]);
+
+        assert_eq!(
+            units.iter().map(Vec::len).collect::<Vec<usize>>(),
+            vec![4, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn builds_screenplay() {
+        let mut story = Story::new("Root");
+        let mut act_1 = Story::new("Act 1");
+        for index in 1..=40 {
+            act_1.push_content(format!(
+                "INT. KITCHEN - NIGHT\n\nMark pours a glass of water.\n\n@MARK\n(whispering)\nIs anyone there? {}",
+                index
+            ));
+        }
+        story.push_part(act_1);
+
+        let mut layout = ManuscriptBuilderLayout::default();
+        layout.screenplay.continued = true;
+        let builder = ManuscriptBuilder::new(layout);
+
+        assert_eq!(
+            builder.element_line_count(&ScreenplayElement::Dialogue("word ".repeat(14))),
+            2
+        );
+
+        let document = builder.build_screenplay(&story).unwrap().build().document;
+        let document = String::from_utf8_lossy(&document);
+        assert!(document.contains("(CONTINUED)"));
+        assert!(document.contains("CONTINUED:"));
+    }
+}
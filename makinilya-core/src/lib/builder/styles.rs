@@ -0,0 +1,335 @@
+//! Named paragraph and character styles of the word document.
+//!
+//! The paragraphs of the story are tagged with styles instead of being formatted directly, so an
+//! editor could restyle the whole manuscript by changing a style. The styles are either defined
+//! from the layout, or taken from the `styles.xml` of a reference document where any style that
+//! it lacks is defined from the layout.
+
+use std::{
+    collections::HashSet,
+    io::{Cursor, Read},
+};
+
+use docx_rs::AlignmentType;
+use xml::reader::{EventReader, XmlEvent};
+use zip::ZipArchive;
+
+use super::{BuilderError, ManuscriptBuilder};
+use crate::{
+    extensions::EscapeXml,
+    units::{HalfPoint, Twip},
+};
+
+pub const NORMAL_STYLE: &str = "Normal";
+pub const TITLE_STYLE: &str = "Title";
+pub const HEADING_1_STYLE: &str = "Heading1";
+pub const HEADING_2_STYLE: &str = "Heading2";
+pub const SCENE_BREAK_STYLE: &str = "SceneBreak";
+pub const CENTERED_STYLE: &str = "Centered";
+pub const FIRST_PARAGRAPH_STYLE: &str = "FirstParagraph";
+pub const BLOCK_QUOTE_STYLE: &str = "BlockQuote";
+pub const TOC_HEADING_STYLE: &str = "TOCHeading";
+pub const FOOTNOTE_TEXT_STYLE: &str = "FootnoteText";
+pub const FOOTNOTE_REFERENCE_STYLE: &str = "FootnoteReference";
+pub const ENDNOTE_TEXT_STYLE: &str = "EndnoteText";
+pub const ENDNOTE_REFERENCE_STYLE: &str = "EndnoteReference";
+
+const STYLES_PATH: &str = "word/styles.xml";
+const WORDPROCESSING_NAMESPACE: &str =
+    "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+const STYLES_NAMESPACES: &str = r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+/// Kinds of the styles that are defined from the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DocxStyleKind {
+    Paragraph,
+    /// Character style of the reference to a note, which is raised above the line.
+    NoteReference,
+}
+
+/// Style that's defined from the layout.
+#[derive(Debug, Clone)]
+struct DocxStyle {
+    kind: DocxStyleKind,
+    id: &'static str,
+    name: &'static str,
+    alignment: AlignmentType,
+    first_line_indention_inch: f32,
+    side_indention_inch: f32,
+    after_line_spacing_point: f32,
+    outline_level: Option<usize>,
+    /// Whether the paragraph is kept on the same page as the paragraph that follows it.
+    keep_with_next: bool,
+}
+
+impl DocxStyle {
+    fn to_xml(&self) -> String {
+        if self.kind == DocxStyleKind::NoteReference {
+            return format!(
+                r#"<w:style w:type="character" w:styleId="{}"><w:name w:val="{}"/><w:rPr><w:vertAlign w:val="superscript"/></w:rPr></w:style>"#,
+                self.id, self.name
+            );
+        }
+
+        let is_normal = self.id == NORMAL_STYLE;
+        let mut xml = format!(
+            r#"<w:style w:type="paragraph"{} w:styleId="{}"><w:name w:val="{}"/>"#,
+            if is_normal { r#" w:default="1""# } else { "" },
+            self.id,
+            self.name
+        );
+
+        if !is_normal {
+            xml.push_str(&format!(
+                r#"<w:basedOn w:val="{}"/><w:next w:val="{}"/>"#,
+                NORMAL_STYLE, NORMAL_STYLE
+            ));
+        }
+
+        xml.push_str("<w:qFormat/><w:pPr>");
+        if self.keep_with_next {
+            xml.push_str("<w:keepNext/>");
+        }
+        let side_indention = match self.side_indention_inch > 0.0 {
+            true => {
+                let twips = Twip::from_inch(self.side_indention_inch).as_f32() as i32;
+                format!(r#" w:left="{0}" w:right="{0}""#, twips)
+            }
+            false => String::new(),
+        };
+        xml.push_str(&format!(
+            r#"<w:spacing w:after="{}"/><w:ind{} w:firstLine="{}"/><w:jc w:val="{}"/>"#,
+            Twip::from_point(self.after_line_spacing_point).as_f32() as i32,
+            side_indention,
+            Twip::from_inch(self.first_line_indention_inch).as_f32() as i32,
+            self.alignment
+        ));
+        if let Some(outline_level) = self.outline_level {
+            xml.push_str(&format!(r#"<w:outlineLvl w:val="{}"/>"#, outline_level));
+        }
+        xml.push_str("</w:pPr></w:style>");
+
+        xml
+    }
+}
+
+/// The ids of the styles that a `styles.xml` defines.
+fn defined_style_ids(styles: &str) -> Result<HashSet<String>, BuilderError> {
+    let mut ids = HashSet::new();
+
+    for event in EventReader::from_str(styles) {
+        let XmlEvent::StartElement {
+            name, attributes, ..
+        } = event?
+        else {
+            continue;
+        };
+        if name.local_name != "style" || name.namespace.as_deref() != Some(WORDPROCESSING_NAMESPACE)
+        {
+            continue;
+        }
+
+        ids.extend(
+            attributes
+                .into_iter()
+                .find(|attribute| attribute.name.local_name == "styleId")
+                .map(|attribute| attribute.value),
+        );
+    }
+
+    Ok(ids)
+}
+
+impl ManuscriptBuilder {
+    fn docx_styles(&self) -> Vec<DocxStyle> {
+        let centered = DocxStyle {
+            kind: DocxStyleKind::Paragraph,
+            id: TITLE_STYLE,
+            name: "Title",
+            alignment: AlignmentType::Center,
+            first_line_indention_inch: 0.0,
+            side_indention_inch: 0.0,
+            after_line_spacing_point: 0.0,
+            outline_level: None,
+            keep_with_next: false,
+        };
+        let heading = DocxStyle {
+            after_line_spacing_point: self.layout.line_spacing_point(),
+            keep_with_next: self.layout.pagination.keep_with_next,
+            ..centered.clone()
+        };
+
+        vec![
+            DocxStyle {
+                id: NORMAL_STYLE,
+                name: "Normal",
+                alignment: AlignmentType::Left,
+                first_line_indention_inch: self.layout.first_line_indention_inch,
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: FIRST_PARAGRAPH_STYLE,
+                name: "First Paragraph",
+                alignment: AlignmentType::Left,
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: BLOCK_QUOTE_STYLE,
+                name: "Block Quote",
+                alignment: AlignmentType::Left,
+                side_indention_inch: self.layout.block_quote_indention_inch,
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: HEADING_1_STYLE,
+                name: "heading 1",
+                outline_level: Some(0),
+                ..heading.clone()
+            },
+            DocxStyle {
+                id: HEADING_2_STYLE,
+                name: "heading 2",
+                outline_level: Some(1),
+                ..heading.clone()
+            },
+            DocxStyle {
+                id: TOC_HEADING_STYLE,
+                name: "TOC Heading",
+                ..heading
+            },
+            DocxStyle {
+                id: SCENE_BREAK_STYLE,
+                name: "Scene Break",
+                keep_with_next: self.layout.pagination.keep_with_next,
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: CENTERED_STYLE,
+                name: "Centered",
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: FOOTNOTE_TEXT_STYLE,
+                name: "footnote text",
+                alignment: AlignmentType::Left,
+                ..centered.clone()
+            },
+            DocxStyle {
+                id: ENDNOTE_TEXT_STYLE,
+                name: "endnote text",
+                alignment: AlignmentType::Left,
+                ..centered.clone()
+            },
+            DocxStyle {
+                kind: DocxStyleKind::NoteReference,
+                id: FOOTNOTE_REFERENCE_STYLE,
+                name: "footnote reference",
+                ..centered.clone()
+            },
+            DocxStyle {
+                kind: DocxStyleKind::NoteReference,
+                id: ENDNOTE_REFERENCE_STYLE,
+                name: "endnote reference",
+                ..centered.clone()
+            },
+            centered,
+        ]
+    }
+
+    /// The document defaults, which carry the typeface, line spacing and widow control of the
+    /// layout.
+    fn docx_style_defaults(&self) -> String {
+        let font_family = self.layout.font_family.escape_xml();
+        let size: usize = HalfPoint::from_point(self.layout.font_size_point).into();
+        let widow_control = match self.layout.pagination.widow_control {
+            true => "<w:widowControl/>",
+            false => r#"<w:widowControl w:val="0"/>"#,
+        };
+
+        format!(
+            r#"<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="{0}" w:hAnsi="{0}" w:eastAsia="{0}" w:cs="{0}"/><w:sz w:val="{1}"/><w:szCs w:val="{1}"/></w:rPr></w:rPrDefault><w:pPrDefault><w:pPr>{3}<w:spacing w:after="0" w:line="{2}" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+            font_family,
+            size,
+            Twip::from_point(self.layout.line_spacing_point()).as_f32() as i32,
+            widow_control
+        )
+    }
+
+    /// Builds the `styles.xml` of the word document. The styles of the reference document are
+    /// kept as they are, and only the styles that it lacks are defined from the layout.
+    pub fn build_docx_styles(&self, reference: Option<&[u8]>) -> Result<Vec<u8>, BuilderError> {
+        let styles = self.docx_styles();
+
+        let Some(reference) = reference else {
+            let definitions: String = styles.iter().map(DocxStyle::to_xml).collect();
+            return Ok(format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles {}>{}{}</w:styles>"#,
+                STYLES_NAMESPACES,
+                self.docx_style_defaults(),
+                definitions
+            )
+            .into_bytes());
+        };
+
+        let mut archive = ZipArchive::new(Cursor::new(reference))?;
+        let mut reference_styles = String::new();
+        archive
+            .by_name(STYLES_PATH)?
+            .read_to_string(&mut reference_styles)?;
+
+        let defined_ids = defined_style_ids(&reference_styles)?;
+        let missing_definitions: String = styles
+            .iter()
+            .filter(|style| !defined_ids.contains(style.id))
+            .map(DocxStyle::to_xml)
+            .collect();
+
+        if let Some(end) = reference_styles.rfind("</w:styles>") {
+            reference_styles.insert_str(end, &missing_definitions);
+        }
+
+        Ok(reference_styles.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod styles_tests {
+    use std::io::Write;
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+    use crate::builder::ManuscriptBuilderLayout;
+
+    #[test]
+    fn builds_docx_styles() {
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout::default());
+
+        let styles = String::from_utf8(builder.build_docx_styles(None).unwrap()).unwrap();
+        assert!(styles.contains(r#"w:default="1" w:styleId="Normal""#));
+        assert!(styles.contains(r#"<w:ind w:firstLine="720"/>"#));
+        assert!(styles.contains(r#"w:styleId="SceneBreak"><w:name w:val="Scene Break"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/>"#));
+        assert!(styles.contains("<w:pPr><w:widowControl/><w:spacing"));
+        assert!(styles.contains(r#"<w:ind w:left="720" w:right="720" w:firstLine="0"/>"#));
+        assert!(styles.contains(r#"<w:style w:type="character" w:styleId="FootnoteReference"><w:name w:val="footnote reference"/><w:rPr><w:vertAlign w:val="superscript"/></w:rPr></w:style>"#));
+
+        let mut reference = ZipWriter::new(Cursor::new(vec![]));
+        reference
+            .start_file(STYLES_PATH, FileOptions::default())
+            .unwrap();
+        reference
+            .write_all(
+                br#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style><w:style w:styleId='Title' w:type='paragraph'/></w:styles>"#,
+            )
+            .unwrap();
+        let reference = reference.finish().unwrap().into_inner();
+
+        let styles =
+            String::from_utf8(builder.build_docx_styles(Some(&reference)).unwrap()).unwrap();
+        assert!(styles.starts_with(r#"<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="Normal"><w:name w:val="Normal"/></w:style><w:style w:styleId='Title' w:type='paragraph'/><w:style"#));
+        assert_eq!(styles.matches(r#"w:styleId="Normal""#).count(), 1);
+        assert!(!styles.contains(r#"w:styleId="Title""#));
+        assert!(styles.contains(r#"w:styleId="Heading1""#));
+    }
+}
@@ -0,0 +1,161 @@
+//! Plain text output of the manuscript for submission forms that only accept pasted text.
+//!
+//! Paragraphs are separated by a blank line instead of being indented, since indentation rarely
+//! survives a paste. Emphasis is marked with underscores, e.g. `_never_`, and notes are numbered
+//! in brackets, e.g. `[1]`. Footnotes are listed at the end of their chapter, and endnotes at the
+//! end of the text. Images are replaced with their description in brackets.
+
+use super::{
+    document::{Block, ManuscriptDocument, Notes, Span},
+    ManuscriptBuilder, ManuscriptBuilderLayout,
+};
+use crate::{config::NotePlacement, story::Story};
+
+impl ManuscriptBuilder {
+    const TEXT_QUOTE_INDENT: &'static str = "    ";
+
+    /// Greedily breaks the text into lines of at most `width` characters at whitespace. A word
+    /// that's longer than the width is placed on its own line.
+    pub(crate) fn wrap_text(text: &str, width: usize) -> String {
+        let mut lines: Vec<String> = vec![];
+        let mut line = String::new();
+
+        for word in text.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+
+        lines.push(line);
+        lines.join("\n")
+    }
+
+    fn text_block(&self, text: &str) -> String {
+        match self.layout.text.wrap_width {
+            Some(width) => Self::wrap_text(text, width),
+            None => text.trim().to_string(),
+        }
+    }
+
+    /// Indents the lines of a block quote, where the indent counts toward the wrap width.
+    fn text_quote(&self, text: &str) -> String {
+        let block = match self.layout.text.wrap_width {
+            Some(width) => Self::wrap_text(
+                text,
+                width.saturating_sub(Self::TEXT_QUOTE_INDENT.len()).max(1),
+            ),
+            None => text.trim().to_string(),
+        };
+
+        block
+            .lines()
+            .map(|line| format!("{}{}", Self::TEXT_QUOTE_INDENT, line))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// The text of a paragraph, with its emphasis marked with underscores and its notes numbered
+    /// in brackets.
+    fn text_inline(text: &str, notes: &mut Notes) -> String {
+        Span::parse(text)
+            .iter()
+            .map(|span| match span {
+                Span::Text(text) => text.clone(),
+                Span::Emphasis(text) => format!("_{}_", text),
+                Span::Note(note) => format!("[{}]", notes.push(note)),
+            })
+            .collect()
+    }
+
+    /// Lists the notes that are yet to be listed, each as a paragraph that starts with its
+    /// number.
+    fn push_text_notes(&self, sections: &mut Vec<String>, notes: &mut Notes) {
+        for (number, note) in notes.take() {
+            let text = Self::text_inline(&note, &mut Notes::default());
+            sections.push(self.text_block(&format!("[{}] {}", number, text)));
+        }
+    }
+
+    pub fn build_text(&self, story: &Story) -> String {
+        let document = ManuscriptDocument::from_story(story, &self.layout);
+        let mut sections: Vec<String> = vec![];
+        let mut notes = Notes::default();
+
+        for block in &document.blocks {
+            match block {
+                Block::PartHeading(_) | Block::ChapterHeading(_) | Block::MatterHeading { .. } => {
+                    if self.layout.notes == NotePlacement::Footnotes {
+                        self.push_text_notes(&mut sections, &mut notes);
+                    }
+                    let lines = block.heading_lines();
+                    if !lines.is_empty() {
+                        sections.push(
+                            lines
+                                .iter()
+                                .map(|line| self.text_block(line))
+                                .collect::<Vec<String>>()
+                                .join("\n"),
+                        );
+                    }
+                }
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                    if text.trim().is_empty() => {}
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                | Block::BlockQuote(text) => {
+                    let text = Self::text_inline(text, &mut notes);
+                    sections.push(match block {
+                        Block::BlockQuote(_) => self.text_quote(&text),
+                        _ => self.text_block(&text),
+                    });
+                }
+                Block::Image(image) => sections.extend(
+                    Self::image_placeholder(image).map(|placeholder| self.text_block(&placeholder)),
+                ),
+                Block::SceneBreak => sections.push(self.layout.text.scene_break.clone()),
+            }
+        }
+
+        if self.layout.notes == NotePlacement::Endnotes && notes.count() > 0 {
+            sections.push(self.text_block(ManuscriptBuilderLayout::DEFAULT_NOTES_TITLE));
+        }
+        self.push_text_notes(&mut sections, &mut notes);
+
+        sections.join("\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod text_tests {
+    use super::*;
+    use crate::builder::TextLayout;
+
+    #[test]
+    fn builds_text() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("I have *never* been to the sea.");
+        chapter_1.push_content("I am Scene #2.");
+        story.push_part(chapter_1);
+
+        let builder = ManuscriptBuilder::new(ManuscriptBuilderLayout {
+            text: TextLayout {
+                wrap_width: Some(16),
+                scene_break: "* * *".into(),
+            },
+            ..Default::default()
+        });
+
+        assert_eq!(
+            builder.build_text(&story),
+            "Chapter 1\n\nI have _never_\nbeen to the sea.\n\n* * *\n\nI am Scene #2.\n"
+        );
+    }
+}
@@ -11,7 +11,9 @@
 //! output_path = "out/manuscript.docx"
 //! [story]
 //! title = "Untitled"
+//! short_title = "UNTITLED"
 //! pen_name = "Brutus Ellis"
+//! language = "en"
 //!
 //! [author]
 //! name = "Brutus Ellis"
@@ -26,9 +28,56 @@
 //! address_2 = "Mandaluyong City"
 //! mobile_number = "+63 908 524 4125"
 //! email_address = "cymonesabina.@email.com"
+//!
+//! [layout]
+//! page_size = "a4"
+//! font_family = "Courier New"
+//!
+//! [word_count]
+//! rounding = "auto"
+//!
+//! [header]
+//! text = "{surname} / {short_title} / {page}"
+//! alignment = "right"
+//!
+//! [numbering]
+//! chapter = "Chapter {number}"
+//! chapter_style = "words"
+//!
+//! [typography]
+//! mode = "smart"
+//!
+//! [table_of_contents]
+//! title = "Contents"
+//!
+//! [docx]
+//! reference = "templates/reference.docx"
+//!
+//! [epub]
+//! cover = "assets/cover.jpg"
+//!
+//! [markdown]
+//! split_chapters = true
+//!
+//! [text]
+//! wrap = 72
+//!
+//! [latex]
+//! class = "memoir"
+//!
+//! [screenplay]
+//! continued = true
+//!
+//! [profiles.beta-readers]
+//! output_path = "out/beta-readers.docx"
+//! chapters = ["Chapter 1", "Chapter 2"]
 //! ```
 
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use serde::Deserialize;
 use thiserror::Error;
@@ -41,6 +90,15 @@ pub enum ConfigError {
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
+
+    #[error("Profile `{0}` is not defined in the config.")]
+    MissingProfile(String),
+
+    #[error("Format `{0}` is not supported.")]
+    UnknownFormat(String),
+
+    #[error("The chapters {0} are not in the draft.")]
+    UnknownChapters(String),
 }
 
 /// General detail configurations of the manuscript.
@@ -48,18 +106,613 @@ pub enum ConfigError {
 pub struct StoryConfig {
     /// The title of the manuscript.
     pub title: Option<String>,
+    /// The abbreviated title of the manuscript that's presented on the running headers.
+    pub short_title: Option<String>,
     /// The pseudonym of the author that's presented on the cover.
     pub pen_name: Option<String>,
+    /// The language of the manuscript as a BCP 47 tag, e.g. `"en"` or `"fr-CA"`.
+    pub language: Option<String>,
+    /// The subject of the manuscript, such as its genre, that's set in the properties of the
+    /// document.
+    pub subject: Option<String>,
+    /// The keywords of the manuscript that are set in the properties of the document.
+    pub keywords: Option<Vec<String>>,
+    /// The kind of narrative that the draft is written as.
+    #[serde(rename = "type")]
+    pub story_type: Option<StoryType>,
+    /// The kinds of the nested directories of the draft, starting with the directories right
+    /// within it, e.g. `["part", "chapter"]`. The kinds are inferred from the draft when left
+    /// undefined, where a directory of directories is a part and any other is a chapter.
+    pub levels: Option<Vec<StoryLevel>>,
+}
+
+/// Kinds of the directories of the draft.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StoryLevel {
+    /// A directory of chapters, which gets a title page of its own.
+    Part,
+    /// A directory of scenes, which gets a heading. The scenes of its nested directories are
+    /// included in it.
+    Chapter,
+}
+
+/// Kinds of narratives that the draft could be written as.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StoryType {
+    /// Fiction that's laid out as a standard manuscript.
+    #[default]
+    Prose,
+    /// A script whose scenes follow the Fountain conventions, and which is laid out in the
+    /// screenplay format.
+    Screenplay,
 }
 
 /// Project structure configurations of the manuscript. The paths should all be relative and must
 /// not have a starting slash `/`.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct ProjectConfig {
     /// The directory where the narrative scenes and chapters are contained.
     pub draft_directory: Option<PathBuf>,
+    /// The directory of the pages, such as a dedication or an epigraph, that come before the
+    /// story. It defaults to `front_matter` and is skipped when it does not exist.
+    pub front_matter_directory: Option<PathBuf>,
+    /// The directory of the pages, such as acknowledgments or an author bio, that come after
+    /// the story. It defaults to `back_matter` and is skipped when it does not exist.
+    pub back_matter_directory: Option<PathBuf>,
+    /// The titles of the pages of matter whose lines are centred instead of shown under a
+    /// heading, compared without regard to case. It defaults to `["Dedication", "Epigraph"]`.
+    pub untitled_matter: Option<Vec<String>>,
     /// The path of the file where the final manuscript is built.
     pub output_path: Option<PathBuf>,
+    /// The file format of the final manuscript. It is inferred from the extension of the
+    /// `output_path` when left undefined.
+    pub format: Option<OutputFormat>,
+    /// The chapters of the draft that are included in the manuscript. Nested chapters are
+    /// selected through their slash-separated path, e.g. `"Part 1/Chapter 2"`. Every chapter is
+    /// included when left undefined.
+    pub chapters: Option<Vec<String>>,
+}
+
+/// File formats that the manuscript could be built into.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Office Open XML word document.
+    Docx,
+    /// Portable Document Format.
+    Pdf,
+    /// EPUB 3 electronic publication.
+    Epub,
+    /// Markdown text, either as a single file or as a directory of chapters.
+    Markdown,
+    /// Standalone HTML page.
+    Html,
+    /// OpenDocument text document.
+    Odt,
+    /// Rich Text Format document.
+    Rtf,
+    /// Plain text.
+    Text,
+    /// LaTeX source.
+    Latex,
+}
+
+impl OutputFormat {
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Docx => "docx",
+            Self::Pdf => "pdf",
+            Self::Epub => "epub",
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Odt => "odt",
+            Self::Rtf => "rtf",
+            Self::Text => "txt",
+            Self::Latex => "tex",
+        }
+    }
+
+    /// Infers the format from the extension of a file path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_lowercase();
+        [
+            Self::Docx,
+            Self::Pdf,
+            Self::Epub,
+            Self::Markdown,
+            Self::Html,
+            Self::Odt,
+            Self::Rtf,
+            Self::Text,
+            Self::Latex,
+        ]
+        .into_iter()
+        .find(|format| format.extension() == extension)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = ConfigError;
+
+    /// Parses the name of a format as it's written in the config, e.g. `latex`, or its file
+    /// extension, e.g. `tex`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.to_lowercase();
+
+        OutputFormat::deserialize(toml::Value::String(name.clone()))
+            .ok()
+            .or(OutputFormat::from_path(
+                &Path::new("manuscript").with_extension(&name),
+            ))
+            .ok_or(ConfigError::UnknownFormat(name))
+    }
+}
+
+/// A named build target of the project.
+///
+/// Every property of a profile is optional and overrides its counterpart from the base
+/// configuration when defined. Sections such as `story` or `author` are merged over their
+/// counterparts property by property.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProfileConfig {
+    /// The path of the file where the profile's manuscript is built.
+    pub output_path: Option<PathBuf>,
+    /// The file format of the profile's manuscript.
+    pub format: Option<OutputFormat>,
+    /// The chapters of the draft that are included in the profile's manuscript.
+    pub chapters: Option<Vec<String>>,
+    /// Variables that are merged over the project's `Context.toml`.
+    pub context: Option<toml::Table>,
+    #[allow(missing_docs)]
+    pub story: Option<StoryConfig>,
+    #[allow(missing_docs)]
+    pub author: Option<ContactInformation>,
+    #[allow(missing_docs)]
+    pub agent: Option<ContactInformation>,
+    #[allow(missing_docs)]
+    pub layout: Option<LayoutConfig>,
+    #[allow(missing_docs)]
+    pub title_page: Option<TitlePageConfig>,
+    #[allow(missing_docs)]
+    pub word_count: Option<WordCountConfig>,
+    #[allow(missing_docs)]
+    pub header: Option<RunningHeadConfig>,
+    #[allow(missing_docs)]
+    pub footer: Option<RunningHeadConfig>,
+    #[allow(missing_docs)]
+    pub numbering: Option<NumberingConfig>,
+    #[allow(missing_docs)]
+    pub typography: Option<TypographyConfig>,
+    #[allow(missing_docs)]
+    pub pagination: Option<PaginationConfig>,
+    #[allow(missing_docs)]
+    pub table_of_contents: Option<TableOfContentsConfig>,
+    #[allow(missing_docs)]
+    pub docx: Option<DocxConfig>,
+    #[allow(missing_docs)]
+    pub epub: Option<EpubConfig>,
+    #[allow(missing_docs)]
+    pub markdown: Option<MarkdownConfig>,
+    #[allow(missing_docs)]
+    pub text: Option<TextConfig>,
+    #[allow(missing_docs)]
+    pub latex: Option<LatexConfig>,
+    #[allow(missing_docs)]
+    pub screenplay: Option<ScreenplayConfig>,
+}
+
+/// Standard paper sizes of the manuscript.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StandardPageSize {
+    /// US Letter, 8.5 by 11 inches.
+    Letter,
+    /// ISO A4, 210 by 297 millimeters.
+    A4,
+}
+
+/// Size of the manuscript's pages. It is either a [`StandardPageSize`] (e.g. `"a4"`) or a custom
+/// `{ width, height }` table in inches.
+#[allow(missing_docs)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum PageSize {
+    Standard(StandardPageSize),
+    Custom { width: f32, height: f32 },
+}
+
+impl PageSize {
+    /// Returns the width and height of the page in inches.
+    pub fn dimensions_inch(&self) -> (f32, f32) {
+        match self {
+            Self::Standard(StandardPageSize::Letter) => (8.5, 11.0),
+            Self::Standard(StandardPageSize::A4) => (8.27, 11.69),
+            Self::Custom { width, height } => (*width, *height),
+        }
+    }
+}
+
+/// Margins of the manuscript's pages in inches.
+#[allow(missing_docs)]
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarginsConfig {
+    pub top: Option<f32>,
+    pub bottom: Option<f32>,
+    pub left: Option<f32>,
+    pub right: Option<f32>,
+}
+
+/// Page geometry and typeface configurations of the manuscript.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LayoutConfig {
+    /// The size of the manuscript's pages.
+    pub page_size: Option<PageSize>,
+    /// The margins of the manuscript's pages.
+    pub margins: Option<MarginsConfig>,
+    /// The typeface of the manuscript's text.
+    pub font_family: Option<String>,
+    /// The size of the manuscript's text in points.
+    pub font_size: Option<f32>,
+    /// The spacing between lines as a multiple of the font size, e.g. `2.0` for double spacing.
+    pub line_spacing: Option<f32>,
+    /// The indention of the first line of every paragraph in inches.
+    pub first_line_indent: Option<f32>,
+    /// The text that separates the scenes of a chapter.
+    pub scene_break: Option<String>,
+    /// How the lines of a scene are read as paragraphs.
+    pub paragraph_break: Option<ParagraphBreak>,
+    /// The indention of both sides of a block quote in inches.
+    pub block_quote_indent: Option<f32>,
+    /// Where the notes of the story are placed.
+    pub notes: Option<NotePlacement>,
+}
+
+/// Models of how the lines of a scene are read as paragraphs.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParagraphBreak {
+    /// Every line is a paragraph.
+    #[default]
+    Line,
+    /// Paragraphs are separated by blank lines, and the lines of a paragraph are joined with a
+    /// space, as in Markdown.
+    BlankLine,
+}
+
+/// Placements of the notes of the story.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotePlacement {
+    /// Every note is placed at the bottom of the page of its reference.
+    #[default]
+    Footnotes,
+    /// The notes are collected at the end of the manuscript.
+    Endnotes,
+}
+
+/// Policies of rounding the word count that's presented on the title page.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WordCountRounding {
+    /// The exact word count.
+    Exact,
+    /// Rounded to the nearest hundred.
+    Hundred,
+    /// Rounded to the nearest thousand.
+    Thousand,
+    /// Rounded to the nearest five thousand.
+    FiveThousand,
+    /// Rounded to the nearest hundred for short fiction, and to the nearest thousand for works
+    /// of 40,000 words or more.
+    Auto,
+}
+
+impl WordCountRounding {
+    const NOVEL_THRESHOLD: u32 = 40_000;
+
+    /// Rounds the word count to the nearest step of the policy. A non-empty count is never
+    /// rounded down to zero.
+    pub fn round(&self, count: u32) -> u32 {
+        let step = match self {
+            Self::Exact => return count,
+            Self::Hundred => 100,
+            Self::Thousand => 1_000,
+            Self::FiveThousand => 5_000,
+            Self::Auto if count < Self::NOVEL_THRESHOLD => 100,
+            Self::Auto => 1_000,
+        };
+
+        match count {
+            0 => 0,
+            _ => ((count + step / 2) / step * step).max(step),
+        }
+    }
+}
+
+/// Word count configurations of the manuscript.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WordCountConfig {
+    /// The rounding policy of the word count.
+    pub rounding: Option<WordCountRounding>,
+    /// The text that precedes a rounded word count, e.g. `"about"`.
+    pub prefix: Option<String>,
+}
+
+/// Configurations of the table of contents, which follows the title page.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TableOfContentsConfig {
+    /// Whether the table of contents is included. It is included when the section is defined,
+    /// unless this is `false`.
+    pub enabled: Option<bool>,
+    /// The heading of the table of contents.
+    pub title: Option<String>,
+}
+
+/// Configurations of the word document output. The paths are relative to the project directory.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DocxConfig {
+    /// The path of a reference document whose named styles (`Normal`, `Title`, `Heading 1`,
+    /// `Heading 2` and `Scene Break`) are used by the manuscript.
+    pub reference: Option<PathBuf>,
+}
+
+/// Configurations of the EPUB output. The paths are relative to the project directory.
+#[derive(Debug, Deserialize, Clone)]
+pub struct EpubConfig {
+    /// The path of the cover image of the publication.
+    pub cover: Option<PathBuf>,
+    /// The path of a stylesheet that replaces the default styles of the publication.
+    pub stylesheet: Option<PathBuf>,
+}
+
+/// Styles of the numbers of chapter and part headings.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberStyle {
+    /// Arabic numerals, e.g. `12`.
+    #[default]
+    Numerals,
+    /// Capitalized English words, e.g. `Twelve`. Numbers beyond `999` are written in numerals.
+    Words,
+    /// Uppercase Roman numerals, e.g. `XII`. Numbers beyond `3999` are written in numerals.
+    Roman,
+}
+
+impl NumberStyle {
+    const ONES: [&'static str; 20] = [
+        "Zero",
+        "One",
+        "Two",
+        "Three",
+        "Four",
+        "Five",
+        "Six",
+        "Seven",
+        "Eight",
+        "Nine",
+        "Ten",
+        "Eleven",
+        "Twelve",
+        "Thirteen",
+        "Fourteen",
+        "Fifteen",
+        "Sixteen",
+        "Seventeen",
+        "Eighteen",
+        "Nineteen",
+    ];
+    const TENS: [&'static str; 10] = [
+        "", "", "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+    ];
+    const ROMAN: [(usize, &'static str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    fn words(number: usize) -> String {
+        match number {
+            0..=19 => Self::ONES[number].to_string(),
+            20..=99 => match number % 10 {
+                0 => Self::TENS[number / 10].to_string(),
+                ones => format!("{}-{}", Self::TENS[number / 10], Self::ONES[ones]),
+            },
+            _ => match number % 100 {
+                0 => format!("{} Hundred", Self::ONES[number / 100]),
+                rest => format!("{} Hundred {}", Self::ONES[number / 100], Self::words(rest)),
+            },
+        }
+    }
+
+    fn roman(mut number: usize) -> String {
+        let mut numeral = String::new();
+
+        for (value, symbol) in Self::ROMAN {
+            while number >= value {
+                numeral.push_str(symbol);
+                number -= value;
+            }
+        }
+
+        numeral
+    }
+
+    /// Writes the number in the style.
+    pub fn format(&self, number: usize) -> String {
+        match self {
+            Self::Words if number < 1000 => Self::words(number),
+            Self::Roman if (1..4000).contains(&number) => Self::roman(number),
+            _ => number.to_string(),
+        }
+    }
+}
+
+/// Numbering configurations of the chapter and part headings.
+///
+/// Chapters and parts are numbered in the order of the draft, so the numbers are never taken
+/// from the names of their directories.
+#[derive(Debug, Deserialize, Clone)]
+pub struct NumberingConfig {
+    /// The template of a chapter's number, where `{number}` is replaced with the number, and
+    /// `{NUMBER}` with the uppercase number, e.g. `"Chapter {number}"`. Chapters are not
+    /// numbered when left undefined.
+    pub chapter: Option<String>,
+    /// The style of a chapter's number.
+    pub chapter_style: Option<NumberStyle>,
+    /// The template of a part's number, e.g. `"Part {number}"`. Parts are not numbered when
+    /// left undefined.
+    pub part: Option<String>,
+    /// The style of a part's number.
+    pub part_style: Option<NumberStyle>,
+    /// Whether the name of a numbered chapter or part is shown on its own line below the
+    /// number. A leading ordinal of the name, such as the `03` of `03 - The Storm`, is omitted.
+    pub titles: Option<bool>,
+}
+
+/// Typography configurations of the interpolated story.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TypographyConfig {
+    /// The transform of the punctuation. The text is kept as it's written when left undefined.
+    pub mode: Option<TypographyMode>,
+}
+
+/// Transforms of the punctuation of the interpolated story.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TypographyMode {
+    /// Straight quotes are curled, `--` and `---` become en and em dashes, and `...` becomes an
+    /// ellipsis. The quotes and spacing follow the language of the story.
+    Smart,
+    /// Curly quotes, dashes, ellipses and non-breaking spaces are written back as plain ASCII.
+    Plain,
+}
+
+/// Title page configurations of the manuscript.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TitlePageConfig {
+    /// The layout of the title page. Defaults to the standard layout.
+    pub variant: Option<TitlePageVariant>,
+}
+
+/// Layouts of the title page.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TitlePageVariant {
+    /// The author's contact information at the top, the title, pen name and word count in the
+    /// middle, and the agent's contact information at the bottom of a page of its own.
+    #[default]
+    Standard,
+    /// The short story layout of William Shunn, where the title and the byline are a third of
+    /// the way down the first page and are followed immediately by the story. The word count is
+    /// across from the author's contact information, and the agent's is left out.
+    ShortStory,
+    /// The novel cover page of William Shunn, where the title and the byline are halfway down a
+    /// page of their own. The word count is across from the author's contact information.
+    Novel,
+    /// The title page of a blind contest, which only has the title and the word count. The pen
+    /// name and the contact information of the author and the agent are stripped from the whole
+    /// manuscript.
+    Anonymous,
+}
+
+/// Pagination configurations of the manuscript.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PaginationConfig {
+    /// Whether the first or last line of a paragraph is kept from being left alone on a page.
+    /// Defaults to true.
+    pub widow_control: Option<bool>,
+    /// Whether headings and scene breaks are kept on the same page as the paragraph that
+    /// follows them. Defaults to true.
+    pub keep_with_next: Option<bool>,
+    /// The page that parts, chapters and the front and back matter start on. Defaults to the
+    /// next page.
+    pub chapter_start: Option<ChapterStart>,
+}
+
+/// Pages that a chapter starts on.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChapterStart {
+    /// The page right after the end of the previous chapter.
+    #[default]
+    NextPage,
+    /// The next odd page, which is the right-hand page of a spread. A blank page is left after
+    /// a previous chapter that ends on an odd page.
+    Recto,
+}
+
+/// Configurations of the Markdown output.
+#[derive(Debug, Deserialize, Clone)]
+pub struct MarkdownConfig {
+    /// Whether every chapter is written to its own file. The output path is then a directory.
+    pub split_chapters: Option<bool>,
+}
+
+/// Configurations of the plain text output.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TextConfig {
+    /// The number of characters after which lines are wrapped. Lines are not wrapped when left
+    /// undefined.
+    pub wrap: Option<usize>,
+    /// The text that separates scenes, which defaults to the layout's scene break.
+    pub scene_break: Option<String>,
+}
+
+/// Configurations of the LaTeX output.
+#[derive(Debug, Deserialize, Clone)]
+pub struct LatexConfig {
+    /// The document class of the source, e.g. `book` or `memoir`.
+    pub class: Option<String>,
+    /// The options of the document class, e.g. `["12pt", "openany"]`.
+    pub class_options: Option<Vec<String>>,
+    /// The path of a template that replaces the default preamble, relative to the project
+    /// directory.
+    pub template: Option<PathBuf>,
+}
+
+/// Configurations of the screenplay output.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScreenplayConfig {
+    /// Whether a scene that runs over a page is marked `(CONTINUED)` at the bottom of the page
+    /// and `CONTINUED:` at the top of the next.
+    pub continued: Option<bool>,
+}
+
+/// Horizontal alignment of a block of text.
+#[allow(missing_docs)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// Configurations of a running header or footer.
+///
+/// The text could contain the fields `{page}`, `{pages}`, `{title}`, `{short_title}`,
+/// `{pen_name}` and `{surname}`, which are replaced with their respective values on every page.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RunningHeadConfig {
+    /// The template of the running text. An empty text disables the running header or footer.
+    pub text: Option<String>,
+    /// The horizontal alignment of the running text.
+    pub alignment: Option<Alignment>,
+    /// Whether or not the running text is also shown on the first page.
+    pub first_page: Option<bool>,
 }
 
 /// Struct representation of a person's contact information.
@@ -73,6 +726,22 @@ pub struct ContactInformation {
     pub email_address: Option<String>,
 }
 
+impl ContactInformation {
+    /// Returns the defined fields of the contact information in the order that they're presented.
+    pub fn lines(&self) -> Vec<&String> {
+        [
+            &self.name,
+            &self.address_1,
+            &self.address_2,
+            &self.mobile_number,
+            &self.email_address,
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
 /// Collective configuration of the crate's executable.
 #[allow(missing_docs)]
 #[derive(Debug, Deserialize, Clone)]
@@ -81,12 +750,33 @@ pub struct Config {
     pub project: Option<ProjectConfig>,
     pub author: Option<ContactInformation>,
     pub agent: Option<ContactInformation>,
+    pub layout: Option<LayoutConfig>,
+    pub title_page: Option<TitlePageConfig>,
+    pub word_count: Option<WordCountConfig>,
+    pub header: Option<RunningHeadConfig>,
+    pub footer: Option<RunningHeadConfig>,
+    pub numbering: Option<NumberingConfig>,
+    pub typography: Option<TypographyConfig>,
+    pub pagination: Option<PaginationConfig>,
+    pub table_of_contents: Option<TableOfContentsConfig>,
+    pub docx: Option<DocxConfig>,
+    pub epub: Option<EpubConfig>,
+    pub markdown: Option<MarkdownConfig>,
+    pub text: Option<TextConfig>,
+    pub latex: Option<LatexConfig>,
+    pub screenplay: Option<ScreenplayConfig>,
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
+    /// The parsed table of the config, over which the sections of a profile are merged.
+    #[serde(skip)]
+    source: toml::Table,
 }
 
 #[doc(hidden)]
 impl Config {
     pub fn parse(source: &str) -> Result<Self, ConfigError> {
-        Ok(toml::from_str(source)?)
+        let mut config: Self = toml::from_str(source)?;
+        config.source = toml::from_str(source)?;
+        Ok(config)
     }
 
     pub fn read(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
@@ -94,4 +784,219 @@ impl Config {
         let config = Config::parse(&file_string)?;
         Ok(config)
     }
+
+    pub fn profile(&self, name: &str) -> Result<&ProfileConfig, ConfigError> {
+        self.profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+            .ok_or(ConfigError::MissingProfile(name.to_string()))
+    }
+
+    pub fn profile_names(&self) -> Vec<&String> {
+        match &self.profiles {
+            Some(profiles) => profiles.keys().collect(),
+            None => vec![],
+        }
+    }
+
+    /// The declared kinds of the nested directories of the draft.
+    pub fn story_levels(&self) -> Option<&Vec<StoryLevel>> {
+        self.story
+            .as_ref()
+            .and_then(|story_config| story_config.levels.as_ref())
+    }
+
+    /// The transform of the punctuation of the story, if any.
+    pub fn typography_mode(&self) -> Option<TypographyMode> {
+        self.typography
+            .as_ref()
+            .and_then(|typography_config| typography_config.mode)
+    }
+
+    /// The kind of narrative of the story, which defaults to prose.
+    pub fn story_type(&self) -> StoryType {
+        self.story
+            .as_ref()
+            .and_then(|story_config| story_config.story_type)
+            .unwrap_or_default()
+    }
+
+    /// Resolves the config of a profile. The sections of the profile are merged over the
+    /// sections of the base config property by property, so a profile that only sets the
+    /// `font_size` of the `layout` keeps every other property of the base `layout`. The
+    /// `output_path`, `format` and `chapters` of the profile override the ones of the `project`.
+    pub fn with_profile(&self, profile_name: &str) -> Result<Self, ConfigError> {
+        self.profile(profile_name)?;
+
+        let mut source = self.source.clone();
+        let mut overrides = match source.remove("profiles") {
+            Some(toml::Value::Table(mut profiles)) => match profiles.remove(profile_name) {
+                Some(toml::Value::Table(profile)) => profile,
+                _ => toml::Table::new(),
+            },
+            _ => toml::Table::new(),
+        };
+        overrides.remove("context");
+
+        let mut project = toml::Table::new();
+        for key in ["output_path", "format", "chapters"] {
+            if let Some(value) = overrides.remove(key) {
+                project.insert(key.into(), value);
+            }
+        }
+        overrides.insert("project".into(), toml::Value::Table(project));
+
+        Self::merge_tables(&mut source, overrides);
+        let mut config: Self = source.clone().try_into()?;
+        config.source = source;
+        Ok(config)
+    }
+
+    /// Merges the values of `overrides` into `table`, where nested tables are merged key by key
+    /// and every other value replaces its counterpart.
+    fn merge_tables(table: &mut toml::Table, overrides: toml::Table) {
+        for (key, value) in overrides {
+            match (
+                table.get_mut(&key).and_then(toml::Value::as_table_mut),
+                value,
+            ) {
+                (Some(nested_table), toml::Value::Table(nested_overrides)) => {
+                    Self::merge_tables(nested_table, nested_overrides)
+                }
+                (_, value) => {
+                    table.insert(key, value);
+                }
+            }
+        }
+    }
+
+    /// Overrides the format of the manuscript. The extension of a configured output path is
+    /// replaced with the format's.
+    pub fn with_format(&self, format: OutputFormat) -> Self {
+        let project = self.project.clone().unwrap_or_default();
+
+        Self {
+            project: Some(ProjectConfig {
+                output_path: project
+                    .output_path
+                    .map(|output_path| match output_path.extension() {
+                        Some(_) => output_path.with_extension(format.extension()),
+                        None => output_path,
+                    }),
+                format: Some(format),
+                ..project
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    const MOCK_CONFIG: &str = r#"[project]
+output_path = "out/manuscript.docx"
+chapters = ["Chapter 1"]
+
+[story]
+title = "Mock Story"
+pen_name = "Mock Pen"
+
+[layout]
+page_size = { width = 6.0, height = 9.0 }
+font_family = "Courier New"
+
+[profiles.beta-readers]
+output_path = "out/beta-readers.docx"
+chapters = ["Chapter 1", "Chapter 2"]
+
+[profiles.beta-readers.story]
+title = "Beta Story"
+
+[profiles.beta-readers.layout]
+page_size = "a4"
+"#;
+
+    #[test]
+    fn resolves_profile() {
+        let config = Config::parse(MOCK_CONFIG).unwrap();
+        let resolved = config.with_profile("beta-readers").unwrap();
+
+        let project = resolved.project.unwrap();
+        assert_eq!(project.output_path, Some("out/beta-readers.docx".into()));
+        assert_eq!(project.chapters.unwrap().len(), 2);
+        let story = resolved.story.unwrap();
+        assert_eq!(story.title, Some("Beta Story".into()));
+        assert_eq!(story.pen_name, Some("Mock Pen".into()));
+        let layout = resolved.layout.unwrap();
+        assert_eq!(
+            layout.page_size,
+            Some(PageSize::Standard(StandardPageSize::A4))
+        );
+        assert_eq!(layout.font_family, Some("Courier New".into()));
+        assert!(resolved.profiles.is_none());
+        assert!(config.with_profile("agent").is_err());
+    }
+
+    #[test]
+    fn rounds_word_count() {
+        assert_eq!(WordCountRounding::Exact.round(1_237), 1_237);
+        assert_eq!(WordCountRounding::Hundred.round(1_237), 1_200);
+        assert_eq!(WordCountRounding::Hundred.round(1_250), 1_300);
+        assert_eq!(WordCountRounding::Hundred.round(37), 100);
+        assert_eq!(WordCountRounding::Thousand.round(81_499), 81_000);
+        assert_eq!(WordCountRounding::FiveThousand.round(82_600), 85_000);
+        assert_eq!(WordCountRounding::Auto.round(7_481), 7_500);
+        assert_eq!(WordCountRounding::Auto.round(92_481), 92_000);
+        assert_eq!(WordCountRounding::Auto.round(0), 0);
+    }
+
+    #[test]
+    fn formats_numbers() {
+        assert_eq!(NumberStyle::Numerals.format(12), "12");
+        assert_eq!(NumberStyle::Words.format(1), "One");
+        assert_eq!(NumberStyle::Words.format(42), "Forty-Two");
+        assert_eq!(NumberStyle::Words.format(300), "Three Hundred");
+        assert_eq!(NumberStyle::Words.format(115), "One Hundred Fifteen");
+        assert_eq!(NumberStyle::Roman.format(4), "IV");
+        assert_eq!(NumberStyle::Roman.format(1994), "MCMXCIV");
+        assert_eq!(NumberStyle::Roman.format(0), "0");
+    }
+
+    #[test]
+    fn infers_output_format() {
+        assert_eq!(
+            OutputFormat::from_path(Path::new("out/manuscript.PDF")),
+            Some(OutputFormat::Pdf)
+        );
+        assert_eq!(
+            OutputFormat::from_path(Path::new("out/manuscript.docx")),
+            Some(OutputFormat::Docx)
+        );
+        assert_eq!(
+            OutputFormat::from_path(Path::new("out/manuscript.md")),
+            Some(OutputFormat::Markdown)
+        );
+        assert_eq!(OutputFormat::from_path(Path::new("out/manuscript")), None);
+        assert_eq!(
+            "latex".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Latex
+        );
+        assert_eq!("TXT".parse::<OutputFormat>().unwrap(), OutputFormat::Text);
+        assert!("doc".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn parses_custom_page_size() {
+        let config = Config::parse(MOCK_CONFIG).unwrap();
+        let page_size = config.layout.unwrap().page_size.unwrap();
+        assert_eq!(page_size.dimensions_inch(), (6.0, 9.0));
+    }
+
+    #[test]
+    fn rejects_missing_profile() {
+        let config = Config::parse(MOCK_CONFIG).unwrap();
+        assert!(config.profile("agent").is_err());
+    }
 }
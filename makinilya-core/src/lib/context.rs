@@ -54,13 +54,13 @@ pub enum Data {
     Object(HashMap<String, Data>),
 }
 
-impl ToString for Data {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for Data {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Boolean(boolean_value) => boolean_value.to_string(),
-            Self::Number(numeric_value) => numeric_value.to_string(),
-            Self::String(string_value) => string_value.to_owned(),
-            Self::Object(object_value) => format!("{:?}", object_value),
+            Self::Boolean(boolean_value) => write!(f, "{}", boolean_value),
+            Self::Number(numeric_value) => write!(f, "{}", numeric_value),
+            Self::String(string_value) => write!(f, "{}", string_value),
+            Self::Object(object_value) => write!(f, "{:?}", object_value),
         }
     }
 }
@@ -72,6 +72,13 @@ pub struct Context {
     pub variables: HashMap<String, Data>,
 }
 
+#[doc(hidden)]
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[doc(hidden)]
 impl Context {
     pub fn new() -> Self {
@@ -112,6 +119,25 @@ impl Context {
         Ok(variables)
     }
 
+    fn merge_variables(target: &mut HashMap<String, Data>, source: HashMap<String, Data>) {
+        for (key, value) in source {
+            match (target.get_mut(&key), value) {
+                (Some(Data::Object(target_object)), Data::Object(source_object)) => {
+                    Self::merge_variables(target_object, source_object);
+                }
+                (_, value) => {
+                    target.insert(key, value);
+                }
+            }
+        }
+    }
+
+    pub fn extend(&mut self, table: Table) -> Result<(), ContextError> {
+        let variables = Self::parse_variables(table)?;
+        Self::merge_variables(&mut self.variables, variables);
+        Ok(())
+    }
+
     pub fn parse(source: &str) -> Result<Self, ContextError> {
         let table = source.parse::<Table>()?;
 
@@ -134,3 +160,36 @@ impl From<HashMap<String, Data>> for Context {
         Self { variables }
     }
 }
+
+#[cfg(test)]
+mod context_tests {
+    use super::*;
+
+    #[test]
+    fn extends_variables() {
+        let mut context = Context::parse(
+            r#"[names]
+author = { first = "Mark", last = "Lopez" }
+"#,
+        )
+        .unwrap();
+        let table = r#"[names.author]
+first = "Marco"
+"#
+        .parse::<Table>()
+        .unwrap();
+
+        context.extend(table).unwrap();
+
+        match context.variables().get("names") {
+            Some(Data::Object(names)) => match names.get("author") {
+                Some(Data::Object(author)) => {
+                    assert_eq!(author.get("first").unwrap().to_string(), "Marco");
+                    assert_eq!(author.get("last").unwrap().to_string(), "Lopez");
+                }
+                _ => panic!("`names.author` should be an object"),
+            },
+            _ => panic!("`names` should be an object"),
+        }
+    }
+}
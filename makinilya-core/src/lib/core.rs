@@ -2,23 +2,36 @@
 //!
 //! # Operations
 //! - [`MakinilyaCore::build()`] - Builds the output manuscript from the project.
+//! - [`MakinilyaCore::build_profile()`] - Builds the output manuscript of a named profile.
+//! - [`MakinilyaCore::build_all()`] - Builds the output manuscripts of every profile.
+//! - [`MakinilyaCore::build_as()`] - Builds the output manuscript in a given format.
 //! - [`MakinilyaCore::new()`] - Creates a new project.
 //! - [`MakinilyaCore::check()`] - Checks all identifiers accessible within the project.
 
-use std::{fs, io::Write, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use colored::Colorize;
 use thiserror::Error;
 
 #[allow(unused_imports)]
 use crate::{
-    builder::{BuilderError, ManuscriptBuilder},
-    config::{Config, ConfigError, ProjectConfig},
+    builder::{
+        document::ManuscriptDocument, epub::EpubAssets, BuilderError, ManuscriptBuilder,
+        ManuscriptBuilderLayout,
+    },
+    config::{Config, ConfigError, OutputFormat, ProfileConfig, ProjectConfig, StoryType},
     context::{Context, ContextError},
     extensions::CloneOnSome,
-    files::ReaderError,
+    files::{File, ReaderError},
+    fountain::Fountain,
     interpolator::StoryInterpolator,
     story::Story,
+    typography::Typographer,
 };
 
 #[allow(missing_docs)]
@@ -44,6 +57,15 @@ pub enum Error {
 
     #[error("[Packing Error]: {0}")]
     Zipper(#[from] zip::result::ZipError),
+
+    #[error(
+        "[Profile Error]: {}",
+        .0.iter()
+            .map(|(profile_name, error)| format!("({}) {}", profile_name, error))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )]
+    Profiles(Vec<(String, Error)>),
 }
 
 /// Encapsulates all static functions of the application's core commands.
@@ -54,7 +76,9 @@ impl MakinilyaCore {
     const CONFIG_FILE_NAME: &'static str = "Config.toml";
     const CONTEXT_FILE_NAME: &'static str = "Context.toml";
     const DEFAULT_DRAFT_DIRECTORY: &'static str = "draft";
-    const DEFAULT_OUTPUT_PATH: &'static str = "out/manuscript.docx";
+    const DEFAULT_FRONT_MATTER_DIRECTORY: &'static str = "front_matter";
+    const DEFAULT_BACK_MATTER_DIRECTORY: &'static str = "back_matter";
+    const DEFAULT_OUTPUT_PATH: &'static str = "out/manuscript";
     const DEFAULT_SCENE: &'static str = r#"Hi, my name is {{ names.mc }}."#;
     const DEFAULT_CONTEXT: &'static str = r#"[names]
 mc = "Core"
@@ -103,7 +127,8 @@ email_address = "cymonesabina.@email.com"
     }
 
     fn init_story(path: impl Into<PathBuf>, config: &Config) -> Result<Story, Error> {
-        let mut draft_directory = path.into();
+        let path_buf: PathBuf = path.into();
+        let mut draft_directory = path_buf.clone();
 
         draft_directory.push(match &config.project {
             Some(project_config) => project_config
@@ -115,31 +140,152 @@ email_address = "cymonesabina.@email.com"
 
         Self::handle_directory(&draft_directory)?;
 
-        Ok(Story::read(draft_directory)?)
+        let mut story = Story::read(draft_directory, config.story_type())?;
+
+        let project_config = config.project.as_ref();
+        let front_matter_directory = path_buf.join(
+            project_config
+                .and_then(|project_config| project_config.front_matter_directory.clone())
+                .unwrap_or(Self::DEFAULT_FRONT_MATTER_DIRECTORY.into()),
+        );
+        if front_matter_directory.is_dir() {
+            for page in Story::read_matter(front_matter_directory)? {
+                story.push_front_matter(page);
+            }
+        }
+
+        let back_matter_directory = path_buf.join(
+            project_config
+                .and_then(|project_config| project_config.back_matter_directory.clone())
+                .unwrap_or(Self::DEFAULT_BACK_MATTER_DIRECTORY.into()),
+        );
+        if back_matter_directory.is_dir() {
+            for page in Story::read_matter(back_matter_directory)? {
+                story.push_back_matter(page);
+            }
+        }
+
+        Ok(story)
     }
 
-    /// The manuscript will be built within the path provided in the `output_path` of the
-    /// `Config.toml`. Refer to [`ProjectConfig`] for more information.
-    pub fn build(path: impl Into<PathBuf>) -> Result<(), Error> {
+    fn init_docx_reference(path: &Path, config: &Config) -> Result<Option<Vec<u8>>, Error> {
+        match config
+            .docx
+            .as_ref()
+            .and_then(|docx_config| docx_config.reference.as_ref())
+        {
+            Some(reference) => Ok(Some(fs::read(path.join(reference))?)),
+            None => Ok(None),
+        }
+    }
+
+    fn init_epub_assets(path: &Path, config: &Config) -> Result<EpubAssets, Error> {
+        let mut assets = EpubAssets::default();
+
+        if let Some(epub_config) = &config.epub {
+            if let Some(cover) = &epub_config.cover {
+                assets.cover = Some(File::read(path.join(cover))?);
+            }
+            if let Some(stylesheet) = &epub_config.stylesheet {
+                assets.stylesheet = Some(fs::read_to_string(path.join(stylesheet))?);
+            }
+        }
+
+        Ok(assets)
+    }
+
+    /// Reads the images that the scenes place, whose paths are relative to the project
+    /// directory.
+    fn init_images(
+        path: &Path,
+        story: &Story,
+        layout: &ManuscriptBuilderLayout,
+    ) -> Result<HashMap<String, Vec<u8>>, Error> {
+        let mut images = HashMap::new();
+
+        for image in ManuscriptDocument::from_story(story, layout).images() {
+            if !images.contains_key(&image.path) {
+                let file = File::read(path.join(&image.path))?;
+                images.insert(image.path.clone(), file.content);
+            }
+        }
+
+        Ok(images)
+    }
+
+    fn init_latex_template(path: &Path, config: &Config) -> Result<Option<String>, Error> {
+        match config
+            .latex
+            .as_ref()
+            .and_then(|latex_config| latex_config.template.as_ref())
+        {
+            Some(template) => Ok(Some(fs::read_to_string(path.join(template))?)),
+            None => Ok(None),
+        }
+    }
+
+    fn build_manuscript(
+        path: impl Into<PathBuf>,
+        config: &Config,
+        context: &Context,
+        label: &str,
+    ) -> Result<(), Error> {
         let path_buf: PathBuf = path.into();
 
-        let config = Self::init_config(path_buf.clone())?;
-        let story = Self::init_story(path_buf.clone(), &config)?;
-        let context = Self::init_context(path_buf.clone())?;
+        let mut story = Self::init_story(path_buf.clone(), config)?;
 
-        let interpolated_story = StoryInterpolator::interpolate(&story, &context)?;
+        if let Some(chapters) = config
+            .project
+            .as_ref()
+            .and_then(|project_config| project_config.chapters.as_ref())
+        {
+            story = story.select(chapters)?;
+        }
 
-        let builder = ManuscriptBuilder::new(&config);
-        let manuscript_document = builder.build_docx(&interpolated_story)?;
+        if let Some(levels) = config.story_levels() {
+            story = story.with_levels(levels);
+        }
 
-        let mut output_path = path_buf;
+        let story_type = config.story_type();
+        if story_type == StoryType::Screenplay {
+            story = story.map_contents(&Fountain::force_elements);
+        }
 
-        output_path.push(match &config.project {
-            Some(project_config) => project_config
-                .output_path
+        let mut interpolated_story = StoryInterpolator::interpolate(&story, context)?;
+
+        if let Some(mode) = config.typography_mode() {
+            let language = config
+                .story
+                .as_ref()
+                .and_then(|story_config| story_config.language.as_deref())
+                .unwrap_or(ManuscriptBuilderLayout::DEFAULT_LANGUAGE);
+            interpolated_story = interpolated_story
+                .map_contents(&|content| Typographer::transform(content, mode, language));
+        }
+
+        let configured_output_path = config
+            .project
+            .as_ref()
+            .and_then(|project_config| project_config.output_path.as_ref());
+        let format = config
+            .project
+            .as_ref()
+            .and_then(|project_config| project_config.format)
+            .or(configured_output_path.and_then(|path| OutputFormat::from_path(path)))
+            .unwrap_or(OutputFormat::Docx);
+        let split_chapters = format == OutputFormat::Markdown
+            && config
+                .markdown
                 .as_ref()
-                .clone_on_some(Self::DEFAULT_OUTPUT_PATH.into()),
-            None => Self::DEFAULT_OUTPUT_PATH.into(),
+                .and_then(|markdown_config| markdown_config.split_chapters)
+                .unwrap_or(false);
+
+        let mut output_path = path_buf.clone();
+
+        output_path.push(match configured_output_path {
+            Some(configured_output_path) => configured_output_path.clone(),
+            None if split_chapters => PathBuf::from(Self::DEFAULT_OUTPUT_PATH),
+            None => PathBuf::from(Self::DEFAULT_OUTPUT_PATH).with_extension(format.extension()),
         });
 
         let mut output_directory = output_path.clone();
@@ -147,21 +293,162 @@ email_address = "cymonesabina.@email.com"
 
         Self::handle_directory(&output_directory)?;
 
-        let file = fs::File::create(&output_path)?;
-        manuscript_document.build().pack(file)?;
+        let mut builder = ManuscriptBuilder::new(config);
+        if story_type != StoryType::Screenplay {
+            builder.images = Self::init_images(&path_buf, &interpolated_story, &builder.layout)?;
+        }
+
+        match format {
+            OutputFormat::Docx if story_type == StoryType::Screenplay => builder
+                .build_screenplay(&interpolated_story)?
+                .build()
+                .pack(fs::File::create(&output_path)?)?,
+            _ if story_type == StoryType::Screenplay => {
+                return Err(BuilderError::UnsupportedScreenplayFormat(
+                    format.extension().to_string(),
+                ))?
+            }
+            OutputFormat::Docx => {
+                let reference = Self::init_docx_reference(&path_buf, config)?;
+                builder
+                    .build_docx(&interpolated_story, reference.as_deref())?
+                    .pack(fs::File::create(&output_path)?)?
+            }
+            OutputFormat::Pdf => fs::write(&output_path, builder.build_pdf(&interpolated_story)?)?,
+            OutputFormat::Epub => {
+                let assets = Self::init_epub_assets(&path_buf, config)?;
+                fs::write(
+                    &output_path,
+                    builder.build_epub(&interpolated_story, &assets)?,
+                )?
+            }
+            OutputFormat::Markdown if split_chapters => {
+                Self::handle_directory(&output_path)?;
+                for file in builder.build_markdown_chapters(&interpolated_story) {
+                    fs::write(output_path.join(file.file_name), file.content)?;
+                }
+            }
+            OutputFormat::Markdown => {
+                fs::write(&output_path, builder.build_markdown(&interpolated_story))?
+            }
+            OutputFormat::Html => fs::write(&output_path, builder.build_html(&interpolated_story))?,
+            OutputFormat::Odt => fs::write(&output_path, builder.build_odt(&interpolated_story)?)?,
+            OutputFormat::Rtf => fs::write(&output_path, builder.build_rtf(&interpolated_story))?,
+            OutputFormat::Text => fs::write(&output_path, builder.build_text(&interpolated_story))?,
+            OutputFormat::Latex => {
+                let template = Self::init_latex_template(&path_buf, config)?;
+                fs::write(
+                    &output_path,
+                    builder.build_latex(&interpolated_story, template.as_deref()),
+                )?
+            }
+        }
 
         println!(
-            "{}{} final manuscript ({})\n",
+            "{}{} {} manuscript ({})\n",
             " ".repeat(3),
             "Built".green().bold(),
+            label,
             output_path.canonicalize()?.to_string_lossy()
         );
 
         Ok(())
     }
 
+    fn build_with_profile(
+        path: impl Into<PathBuf>,
+        config: &Config,
+        profile_name: &str,
+        format: Option<OutputFormat>,
+    ) -> Result<(), Error> {
+        let path_buf: PathBuf = path.into();
+        let profile: &ProfileConfig = config.profile(profile_name)?;
+
+        let mut context = Self::init_context(path_buf.clone())?;
+        if let Some(context_table) = &profile.context {
+            context.extend(context_table.clone())?;
+        }
+
+        let mut profile_config = config.with_profile(profile_name)?;
+        if let Some(format) = format {
+            profile_config = profile_config.with_format(format);
+        }
+
+        Self::build_manuscript(path_buf, &profile_config, &context, profile_name)
+    }
+
+    /// The manuscript will be built within the path provided in the `output_path` of the
+    /// `Config.toml`. Refer to [`ProjectConfig`] for more information.
+    pub fn build(path: impl Into<PathBuf>) -> Result<(), Error> {
+        let path_buf: PathBuf = path.into();
+
+        let config = Self::init_config(path_buf.clone())?;
+        let context = Self::init_context(path_buf.clone())?;
+
+        Self::build_manuscript(path_buf, &config, &context, "final")
+    }
+
+    /// Builds the manuscript of the profile defined under `[profiles.<profile_name>]` in the
+    /// `Config.toml`. Refer to [`ProfileConfig`] for more information.
+    pub fn build_profile(path: impl Into<PathBuf>, profile_name: &str) -> Result<(), Error> {
+        let path_buf: PathBuf = path.into();
+        let config = Self::init_config(path_buf.clone())?;
+
+        Self::build_with_profile(path_buf, &config, profile_name, None)
+    }
+
+    /// Builds the manuscript, or the manuscript of a named profile, in the given format instead
+    /// of the configured one. The configured output path keeps its location with the format's
+    /// extension.
+    pub fn build_as(
+        path: impl Into<PathBuf>,
+        profile_name: Option<&str>,
+        format: OutputFormat,
+    ) -> Result<(), Error> {
+        let path_buf: PathBuf = path.into();
+        let config = Self::init_config(path_buf.clone())?;
+
+        match profile_name {
+            Some(profile_name) => {
+                Self::build_with_profile(path_buf, &config, profile_name, Some(format))
+            }
+            None => {
+                let context = Self::init_context(path_buf.clone())?;
+                Self::build_manuscript(path_buf, &config.with_format(format), &context, "final")
+            }
+        }
+    }
+
+    /// Builds the manuscripts of every profile in the `Config.toml` in a single run. The default
+    /// manuscript is built instead when the project has no profiles. A profile that fails does
+    /// not stop the run, and the errors of every failed profile are returned together.
+    pub fn build_all(path: impl Into<PathBuf>) -> Result<(), Error> {
+        let path_buf: PathBuf = path.into();
+        let config = Self::init_config(path_buf.clone())?;
+        let profile_names = config.profile_names();
+
+        if profile_names.is_empty() {
+            return Self::build(path_buf);
+        }
+
+        let errors: Vec<(String, Error)> = profile_names
+            .into_iter()
+            .filter_map(|profile_name| {
+                Self::build_with_profile(path_buf.clone(), &config, profile_name, None)
+                    .err()
+                    .map(|error| (profile_name.clone(), error))
+            })
+            .collect();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Profiles(errors)),
+        }
+    }
+
     /// Creates project files from directory path. The resulting project will have a defaulted
     /// `Config.toml` and `Context.toml` files, as well as a scene and chapter.
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(path: impl Into<PathBuf>) -> Result<(), Error> {
         let base_directory: PathBuf = path.into();
 
@@ -222,7 +509,7 @@ email_address = "cymonesabina.@email.com"
             println!("{}{}", " ".repeat(6), identifier);
         }
 
-        println!("");
+        println!();
 
         Ok(())
     }
@@ -239,6 +526,47 @@ mod core_tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn builds_profile_manuscript() {
+        let path = std::env::current_dir().unwrap();
+        let result =
+            MakinilyaCore::build_profile(path.join("mock/01-standard-project"), "beta-readers");
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn builds_all_manuscripts() {
+        let path = std::env::current_dir().unwrap();
+        let result = MakinilyaCore::build_all(path.join("mock/01-standard-project"));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn builds_manuscript_as_format() {
+        let path = std::env::current_dir().unwrap();
+        let result = MakinilyaCore::build_as(
+            path.join("mock/01-standard-project"),
+            Some("agent"),
+            OutputFormat::Latex,
+        );
+        assert!(result.is_ok());
+        assert!(path.join("mock/01-standard-project/out/agent.tex").exists());
+    }
+
+    #[test]
+    fn builds_screenplay_manuscript() {
+        let path = std::env::current_dir().unwrap();
+        let result = MakinilyaCore::build(path.join("mock/03-screenplay-project"));
+        assert!(result.is_ok());
+
+        let result = MakinilyaCore::build_as(
+            path.join("mock/03-screenplay-project"),
+            None,
+            OutputFormat::Pdf,
+        );
+        assert!(result.is_err());
+    }
+
     #[test]
     fn new_project() {
         let path = std::env::current_dir().unwrap();
@@ -1,5 +1,7 @@
 #![doc(hidden)]
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use docx_rs::{Paragraph, TableCell};
 
 /// Optionally renders a paragraph to a document structure.
@@ -53,3 +55,118 @@ impl<T: Clone> CloneOnSome<T> for Option<&T> {
         self.map_or(default, |some| some.clone())
     }
 }
+
+/// Escapes the special characters of XML (`&`, `<`, `>`, `"` and `'`) into entities.
+pub trait EscapeXml {
+    #[allow(missing_docs)]
+    fn escape_xml(&self) -> String;
+}
+
+impl EscapeXml for str {
+    fn escape_xml(&self) -> String {
+        let mut escaped = String::with_capacity(self.len());
+
+        for character in self.chars() {
+            match character {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(character),
+            }
+        }
+
+        escaped
+    }
+}
+
+/// Converts text into a lowercase, dash-separated identifier that's safe for file names and
+/// URLs, e.g. `Chapter 1: Déjà Vu` into `chapter-1-déjà-vu`.
+pub trait Slugify {
+    #[allow(missing_docs)]
+    fn slugify(&self) -> String;
+}
+
+impl Slugify for str {
+    fn slugify(&self) -> String {
+        self.to_lowercase()
+            .split(|character: char| !character.is_alphanumeric())
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<&str>>()
+            .join("-")
+    }
+}
+
+/// Formats a time to a UTC timestamp in the ISO 8601 format, e.g. `2023-12-01T08:30:00Z`.
+///
+/// The calendar date is derived from the days since the Unix epoch with Howard Hinnant's
+/// `civil_from_days` algorithm.
+pub trait ToIso8601 {
+    #[allow(missing_docs)]
+    fn to_iso8601(&self) -> String;
+}
+
+impl ToIso8601 for SystemTime {
+    fn to_iso8601(&self) -> String {
+        let seconds = self
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as i64);
+        let (days, seconds_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+
+        let shifted_days = days + 719_468;
+        let era = shifted_days.div_euclid(146_097);
+        let day_of_era = shifted_days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            seconds_of_day / 3_600,
+            seconds_of_day % 3_600 / 60,
+            seconds_of_day % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod extensions_tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            "<a href=\"x\">&</a>".escape_xml(),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn slugifies() {
+        assert_eq!("Chapter 1: Déjà Vu".slugify(), "chapter-1-déjà-vu");
+        assert_eq!("  --Part  2-- ".slugify(), "part-2");
+    }
+
+    #[test]
+    fn formats_iso8601() {
+        assert_eq!(UNIX_EPOCH.to_iso8601(), "1970-01-01T00:00:00Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_701_419_400);
+        assert_eq!(time.to_iso8601(), "2023-12-01T08:30:00Z");
+        let time = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        assert_eq!(time.to_iso8601(), "2000-02-29T00:00:00Z");
+    }
+}
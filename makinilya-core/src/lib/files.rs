@@ -1,9 +1,12 @@
 #![doc(hidden)]
 
 use std::{
+    cmp::Ordering,
     fs::{self},
     io::Read,
+    iter::Peekable,
     path::PathBuf,
+    str::Chars,
 };
 
 use thiserror::Error;
@@ -35,6 +38,39 @@ pub struct File {
     pub extension: Option<String>,
 }
 
+impl File {
+    pub fn read(path: impl Into<PathBuf>) -> Result<Self, ReaderError> {
+        let path: PathBuf = path.into();
+        let name = path
+            .file_name()
+            .ok_or(ReaderError::FileName {
+                file_path: path.clone(),
+            })?
+            .to_string_lossy()
+            .to_string();
+
+        let mut file = fs::File::open(&path).map_err(|_error| ReaderError::OpenFile {
+            file_name: name.clone(),
+        })?;
+
+        let mut content: Vec<u8> = vec![];
+        file.read_to_end(&mut content)
+            .map_err(|_error| ReaderError::ReadFile {
+                file_name: name.clone(),
+            })?;
+
+        let extension = path
+            .extension()
+            .map(|os_string| os_string.to_string_lossy().to_string());
+
+        Ok(Self {
+            name,
+            content,
+            extension,
+        })
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub enum PathItem {
@@ -42,6 +78,61 @@ pub enum PathItem {
     Directory(Box<Directory>),
 }
 
+impl PathItem {
+    #[allow(missing_docs)]
+    pub fn name(&self) -> &String {
+        match self {
+            Self::File(file) => &file.name,
+            Self::Directory(directory) => &directory.name,
+        }
+    }
+}
+
+/// Takes a run of digits without its leading zeros.
+fn take_digits(characters: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = characters.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits.trim_start_matches('0').to_string()
+}
+
+/// Compares names in their natural order, where runs of digits are compared by their value, so
+/// `Chapter 2` comes before `Chapter 10`.
+pub fn compare_names(a: &str, b: &str) -> Ordering {
+    let mut a_characters = a.chars().peekable();
+    let mut b_characters = b.chars().peekable();
+
+    loop {
+        match (a_characters.peek(), b_characters.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_character), Some(b_character))
+                if a_character.is_ascii_digit() && b_character.is_ascii_digit() =>
+            {
+                let a_digits = take_digits(&mut a_characters);
+                let b_digits = take_digits(&mut b_characters);
+                let ordering = a_digits
+                    .len()
+                    .cmp(&b_digits.len())
+                    .then_with(|| a_digits.cmp(&b_digits));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_character), Some(b_character)) => {
+                let ordering = a_character.cmp(b_character);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_characters.next();
+                b_characters.next();
+            }
+        }
+    }
+}
+
 #[allow(missing_docs)]
 #[derive(Debug)]
 pub struct Directory {
@@ -73,6 +164,8 @@ impl Directory {
         self.contents.push(path_item);
     }
 
+    /// Reads the directory recursively, where the contents are sorted in the natural order of
+    /// their names.
     pub fn read(path: impl Into<PathBuf>) -> Result<Self, ReaderError> {
         let path: PathBuf = path.into();
         let read_dir = fs::read_dir(&path).map_err(|_error| ReaderError::Directory {
@@ -102,32 +195,42 @@ impl Directory {
                 let nested_directory = Self::read(entry_path)?;
                 directory.push_item(PathItem::Directory(Box::new(nested_directory)))
             } else {
-                let name = entry.file_name().to_string_lossy().to_string();
-                let mut file =
-                    fs::File::open(&entry_path).map_err(|_error| ReaderError::OpenFile {
-                        file_name: name.clone(),
-                    })?;
-
-                let mut content: Vec<u8> = vec![];
-                file.read_to_end(&mut content)
-                    .map_err(|_error| ReaderError::ReadFile {
-                        file_name: name.clone(),
-                    })?;
-
-                let extension = entry
-                    .path()
-                    .extension()
-                    .map(|os_string| os_string.to_string_lossy().to_string());
-                let nested_file = File {
-                    content,
-                    name,
-                    extension,
-                };
-
+                let nested_file = File::read(entry_path)?;
                 directory.push_item(PathItem::File(nested_file));
             }
         }
 
+        directory
+            .contents
+            .sort_by(|a, b| compare_names(a.name(), b.name()));
+
         Ok(directory)
     }
 }
+
+#[cfg(test)]
+mod files_tests {
+    use super::*;
+
+    #[test]
+    fn compares_names_naturally() {
+        let mut names = vec![
+            "Chapter 10",
+            "Chapter 2",
+            "Chapter 1",
+            "Appendix",
+            "Chapter 02b",
+        ];
+        names.sort_by(|a, b| compare_names(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "Appendix",
+                "Chapter 1",
+                "Chapter 2",
+                "Chapter 02b",
+                "Chapter 10"
+            ]
+        );
+    }
+}
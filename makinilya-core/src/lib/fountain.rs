@@ -0,0 +1,307 @@
+#![doc(hidden)]
+
+//! Parses the scenes of a screenplay that are written in the [Fountain](https://fountain.io)
+//! conventions.
+//!
+//! The elements of a script are mostly told apart by their case and position, e.g. a character
+//! cue is an uppercase line with dialogue right below it. Since the cue could also be a variable
+//! that's only known after interpolation, [`Fountain::force_elements`] marks every detected
+//! element with its forcing prefix (`.`, `@` or `>`) before the scene is interpolated. The
+//! interpolated scene is then parsed with [`Fountain::parse`].
+//!
+//! Title pages, notes (`[[ ]]`), boneyards (`/* */`), sections (`#`) and synopses (`=`) are
+//! omitted, since the title page is generated from the config.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScreenplayElement {
+    SceneHeading(String),
+    Action(String),
+    Character(String),
+    Parenthetical(String),
+    Dialogue(String),
+    Transition(String),
+    Centered(String),
+    PageBreak,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BlockKind {
+    SceneHeading,
+    Transition,
+    Centered,
+    Dialogue,
+    PageBreak,
+    Omitted,
+    Action,
+}
+
+pub struct Fountain;
+
+impl Fountain {
+    const SCENE_HEADING_PREFIXES: [&'static str; 9] = [
+        "int./ext", "int/ext", "i/e", "int.", "ext.", "est.", "int ", "ext ", "est ",
+    ];
+    const TITLE_PAGE_KEYS: [&'static str; 10] = [
+        "title",
+        "credit",
+        "author",
+        "authors",
+        "source",
+        "draft date",
+        "date",
+        "contact",
+        "copyright",
+        "notes",
+    ];
+
+    /// Removes the interpolated variables (`{{ }}`) of a line, and tells whether it had any.
+    fn without_variables(line: &str) -> (String, bool) {
+        let mut text = String::new();
+        let mut has_variables = false;
+        let mut rest = line;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}") else {
+                break;
+            };
+            text.push_str(&rest[..start]);
+            rest = &rest[start + end + 2..];
+            has_variables = true;
+        }
+
+        text.push_str(rest);
+        (text, has_variables)
+    }
+
+    fn is_uppercase(text: &str) -> bool {
+        text.chars().any(char::is_alphabetic)
+            && !text.chars().any(|character| character.is_lowercase())
+    }
+
+    fn is_scene_heading(line: &str) -> bool {
+        let lowercase_line = line.to_lowercase();
+        (line.starts_with('.') && !line.starts_with(".."))
+            || Self::SCENE_HEADING_PREFIXES
+                .iter()
+                .any(|prefix| lowercase_line.starts_with(prefix))
+    }
+
+    fn is_transition(line: &str) -> bool {
+        (line.starts_with('>') && !line.ends_with('<'))
+            || (Self::is_uppercase(&Self::without_variables(line).0) && line.ends_with("TO:"))
+    }
+
+    /// A character cue is an uppercase line, where extensions such as `(cont'd)` and variables
+    /// are disregarded. A cue that's only a variable is also uppercase.
+    fn is_character(line: &str) -> bool {
+        if line.starts_with('@') {
+            return true;
+        }
+
+        let name = line
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('^');
+        let (name, has_variables) = Self::without_variables(name);
+
+        match name.trim().is_empty() {
+            true => has_variables,
+            false => Self::is_uppercase(&name),
+        }
+    }
+
+    fn block_kind(lines: &[&str]) -> BlockKind {
+        let first_line = lines[0].trim();
+
+        if first_line.len() >= 3 && first_line.chars().all(|character| character == '=') {
+            BlockKind::PageBreak
+        } else if first_line.starts_with('#') || first_line.starts_with('=') {
+            BlockKind::Omitted
+        } else if first_line.starts_with('!') {
+            BlockKind::Action
+        } else if lines.len() == 1 && first_line.starts_with('>') && first_line.ends_with('<') {
+            BlockKind::Centered
+        } else if lines.len() == 1 && Self::is_scene_heading(first_line) {
+            BlockKind::SceneHeading
+        } else if lines.len() == 1 && Self::is_transition(first_line) {
+            BlockKind::Transition
+        } else if lines.len() > 1 && Self::is_character(first_line) {
+            BlockKind::Dialogue
+        } else {
+            BlockKind::Action
+        }
+    }
+
+    fn is_title_page(lines: &[&str]) -> bool {
+        lines[0].split_once(':').is_some_and(|(key, _)| {
+            Self::TITLE_PAGE_KEYS.contains(&key.trim().to_lowercase().as_str())
+        })
+    }
+
+    /// Splits the source into blocks of lines that are separated by blank lines.
+    fn blocks(source: &str) -> Vec<Vec<&str>> {
+        let mut blocks: Vec<Vec<&str>> = vec![];
+        let mut block: Vec<&str> = vec![];
+
+        for line in source.lines() {
+            if line.trim().is_empty() {
+                if !block.is_empty() {
+                    blocks.push(std::mem::take(&mut block));
+                }
+            } else {
+                block.push(line.trim_end());
+            }
+        }
+
+        if !block.is_empty() {
+            blocks.push(block);
+        }
+
+        blocks
+    }
+
+    /// Removes the notes and the boneyard of the source.
+    fn strip_comments(source: &str) -> String {
+        let mut stripped = source.replace("\r\n", "\n");
+
+        for (open, close) in [("/*", "*/"), ("[[", "]]")] {
+            while let Some(start) = stripped.find(open) {
+                let end = stripped[start..]
+                    .find(close)
+                    .map_or(stripped.len(), |end| start + end + close.len());
+                stripped.replace_range(start..end, "");
+            }
+        }
+
+        stripped
+    }
+
+    /// Marks every scene heading, transition and character cue with its forcing prefix, so the
+    /// elements are kept after interpolation.
+    pub fn force_elements(source: &str) -> String {
+        let blocks = Self::blocks(source);
+        let mut forced_blocks: Vec<String> = vec![];
+
+        for (index, lines) in blocks.iter().enumerate() {
+            let first_line = lines[0].trim_start();
+            let prefix = match Self::block_kind(lines) {
+                _ if index == 0 && Self::is_title_page(lines) => "",
+                BlockKind::SceneHeading if !first_line.starts_with('.') => ".",
+                BlockKind::Transition if !first_line.starts_with('>') => ">",
+                BlockKind::Dialogue if !first_line.starts_with('@') => "@",
+                _ => "",
+            };
+
+            let mut forced_lines = vec![format!("{}{}", prefix, first_line)];
+            forced_lines.extend(lines[1..].iter().map(|line| line.to_string()));
+            forced_blocks.push(forced_lines.join("\n"));
+        }
+
+        forced_blocks.join("\n\n")
+    }
+
+    pub fn parse(source: &str) -> Vec<ScreenplayElement> {
+        let source = Self::strip_comments(source);
+        let mut elements = vec![];
+
+        for (index, lines) in Self::blocks(&source).iter().enumerate() {
+            if index == 0 && Self::is_title_page(lines) {
+                continue;
+            }
+
+            let first_line = lines[0].trim();
+
+            match Self::block_kind(lines) {
+                BlockKind::PageBreak => elements.push(ScreenplayElement::PageBreak),
+                BlockKind::Omitted => (),
+                BlockKind::SceneHeading => elements.push(ScreenplayElement::SceneHeading(
+                    first_line.trim_start_matches('.').trim().to_uppercase(),
+                )),
+                BlockKind::Transition => elements.push(ScreenplayElement::Transition(
+                    first_line.trim_start_matches('>').trim().to_uppercase(),
+                )),
+                BlockKind::Centered => elements.push(ScreenplayElement::Centered(
+                    first_line
+                        .trim_start_matches('>')
+                        .trim_end_matches('<')
+                        .trim()
+                        .to_string(),
+                )),
+                BlockKind::Dialogue => {
+                    let cue = first_line.trim_start_matches('@').trim_end_matches('^');
+                    elements.push(ScreenplayElement::Character(cue.trim().to_uppercase()));
+
+                    for line in &lines[1..] {
+                        let line = line.trim();
+                        elements.push(match line.starts_with('(') && line.ends_with(')') {
+                            true => ScreenplayElement::Parenthetical(line.to_string()),
+                            false => ScreenplayElement::Dialogue(line.to_string()),
+                        });
+                    }
+                }
+                BlockKind::Action => {
+                    let mut action_lines: Vec<String> =
+                        lines.iter().map(|line| line.to_string()).collect();
+                    action_lines[0] = first_line.trim_start_matches('!').to_string();
+                    elements.push(ScreenplayElement::Action(action_lines.join("\n")));
+                }
+            }
+        }
+
+        elements
+    }
+}
+
+#[cfg(test)]
+mod fountain_tests {
+    use super::*;
+
+    const MOCK_SCENE: &str = "Title: Mock Story\nAuthor: Brutus Ellis
+
+INT. KITCHEN - NIGHT
+
+{{ names.mc }} pours a glass of water. [[ Maybe milk? ]]
+
+{{ names.mc }}
+(whispering)
+Is anyone there?
+
+MARY (O.S.)
+Only me.
+
+CUT TO:
+
+> THE END <";
+
+    #[test]
+    fn forces_elements() {
+        let forced = Fountain::force_elements(MOCK_SCENE);
+
+        assert!(forced.contains("\n\n.INT. KITCHEN - NIGHT\n\n"));
+        assert!(forced.contains("\n\n@{{ names.mc }}\n(whispering)\n"));
+        assert!(forced.contains("\n\n@MARY (O.S.)\nOnly me."));
+        assert!(forced.contains("\n\n>CUT TO:\n\n"));
+        assert!(forced.contains("\n\n{{ names.mc }} pours a glass of water."));
+    }
+
+    #[test]
+    fn parses_elements() {
+        let interpolated = Fountain::force_elements(MOCK_SCENE).replace("{{ names.mc }}", "Mark");
+
+        assert_eq!(
+            Fountain::parse(&interpolated),
+            vec![
+                ScreenplayElement::SceneHeading("INT. KITCHEN - NIGHT".into()),
+                ScreenplayElement::Action("Mark pours a glass of water.".into()),
+                ScreenplayElement::Character("MARK".into()),
+                ScreenplayElement::Parenthetical("(whispering)".into()),
+                ScreenplayElement::Dialogue("Is anyone there?".into()),
+                ScreenplayElement::Character("MARY (O.S.)".into()),
+                ScreenplayElement::Dialogue("Only me.".into()),
+                ScreenplayElement::Transition("CUT TO:".into()),
+                ScreenplayElement::Centered("THE END".into()),
+            ]
+        );
+    }
+}
@@ -15,23 +15,25 @@ impl StoryInterpolator {
         let mut checked_story: Vec<String> = Vec::new();
 
         for content in story.contents() {
-            let parsed_source = MakinilyaText::parse(&content)?.next().unwrap();
+            let parsed_source = MakinilyaText::parse(content)?.next().unwrap();
             let expressions = parsed_source.into_inner();
 
             for expression in expressions {
                 if let Some(expression_value) = expression.into_inner().next() {
-                    match expression_value.as_rule() {
-                        Rule::string_interpolation => {
-                            let identifier = expression_value.into_inner().next().unwrap().as_str();
-                            checked_story.push(identifier.to_string());
-                        }
-                        _ => (),
+                    if expression_value.as_rule() == Rule::string_interpolation {
+                        let identifier = expression_value.into_inner().next().unwrap().as_str();
+                        checked_story.push(identifier.to_string());
                     }
                 }
             }
         }
 
-        for part in story.parts() {
+        for part in story
+            .parts()
+            .iter()
+            .chain(story.front_matter())
+            .chain(story.back_matter())
+        {
             let mut checked_part = Self::check(part)?;
             checked_story.append(&mut checked_part);
         }
@@ -41,9 +43,10 @@ impl StoryInterpolator {
 
     pub fn interpolate(story: &Story, context: &Context) -> Result<Story, Error> {
         let mut interpolated_story = Story::new(story.title());
+        interpolated_story.set_kind(story.kind());
 
         for content in story.contents() {
-            let parsed_source = MakinilyaText::parse(&content)?.next().unwrap();
+            let parsed_source = MakinilyaText::parse(content)?.next().unwrap();
             let expressions = parsed_source.into_inner();
 
             let interpolated_expressions: Vec<String> = expressions
@@ -58,6 +61,14 @@ impl StoryInterpolator {
             interpolated_story.push_part(interpolated_part);
         }
 
+        for page in story.front_matter() {
+            interpolated_story.push_front_matter(Self::interpolate(page, context)?);
+        }
+
+        for page in story.back_matter() {
+            interpolated_story.push_back_matter(Self::interpolate(page, context)?);
+        }
+
         Ok(interpolated_story)
     }
 
@@ -77,14 +88,9 @@ impl StoryInterpolator {
                     let first_identifier = identifier_array.next().unwrap();
                     let mut data = context.variables().get(first_identifier);
 
-                    while let Some(identifier) = identifier_array.next() {
-                        if let Some(unwrapped_data) = data {
-                            match unwrapped_data {
-                                Data::Object(object_value) => {
-                                    data = object_value.get(identifier);
-                                }
-                                _ => (),
-                            }
+                    for identifier in identifier_array {
+                        if let Some(Data::Object(object_value)) = data {
+                            data = object_value.get(identifier);
                         }
                     }
 
@@ -7,6 +7,9 @@ pub mod context;
 pub mod core;
 pub mod extensions;
 pub mod files;
+pub mod fountain;
 pub mod interpolator;
 pub mod story;
+pub mod typography;
 pub mod units;
+pub mod word_count;
@@ -2,43 +2,96 @@
 
 use std::path::PathBuf;
 
-use crate::files::{Directory, PathItem, ReaderError};
+use crate::{
+    config::{ConfigError, StoryLevel, StoryType},
+    files::{compare_names, Directory, File, PathItem, ReaderError},
+};
 
 pub const MAKINILYA_TEXT_EXTENSION: &str = "mt";
+pub const FOUNTAIN_EXTENSION: &str = "fountain";
+
+/// Kinds of the nodes of the story tree, whose scenes are the contents of the nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StoryKind {
+    /// The whole draft, whose own scenes open the story without a heading.
+    Book,
+    /// A directory of chapters, whose own scenes are shown on its title page.
+    Part,
+    /// A directory of scenes that are separated by scene breaks.
+    #[default]
+    Chapter,
+}
+
+impl From<StoryLevel> for StoryKind {
+    fn from(value: StoryLevel) -> Self {
+        match value {
+            StoryLevel::Part => Self::Part,
+            StoryLevel::Chapter => Self::Chapter,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Story {
     title: String,
-    parts: Vec<Box<Story>>,
+    kind: StoryKind,
+    parts: Vec<Story>,
     contents: Vec<String>,
+    front_matter: Vec<Story>,
+    back_matter: Vec<Story>,
 }
 
 impl Story {
     pub fn new(title: impl Into<String>) -> Self {
         Self {
             title: title.into(),
+            kind: StoryKind::default(),
             parts: vec![],
             contents: vec![],
+            front_matter: vec![],
+            back_matter: vec![],
         }
     }
 
+    /// Adds a nested story, which makes a chapter a part.
     pub fn push_part(&mut self, part: Story) {
-        self.parts.push(Box::new(part));
+        if self.kind == StoryKind::Chapter {
+            self.kind = StoryKind::Part;
+        }
+        self.parts.push(part);
     }
 
     pub fn push_content(&mut self, source: impl Into<String>) {
         self.contents.push(source.into());
     }
 
+    /// Adds a page, such as a dedication or an epigraph, that comes before the story.
+    pub fn push_front_matter(&mut self, page: Story) {
+        self.front_matter.push(page);
+    }
+
+    /// Adds a page, such as acknowledgments or an author bio, that comes after the story.
+    pub fn push_back_matter(&mut self, page: Story) {
+        self.back_matter.push(page);
+    }
+
     pub fn title(&self) -> &String {
         &self.title
     }
 
-    pub fn parts(&self) -> &Vec<Box<Story>> {
+    pub fn kind(&self) -> StoryKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: StoryKind) {
+        self.kind = kind;
+    }
+
+    pub fn parts(&self) -> &Vec<Story> {
         &self.parts
     }
 
-    pub fn mut_parts(&mut self) -> &mut Vec<Box<Story>> {
+    pub fn mut_parts(&mut self) -> &mut Vec<Story> {
         &mut self.parts
     }
 
@@ -50,18 +103,153 @@ impl Story {
         &mut self.contents
     }
 
-    pub fn parse(directory: &Directory) -> Self {
+    pub fn front_matter(&self) -> &Vec<Story> {
+        &self.front_matter
+    }
+
+    pub fn back_matter(&self) -> &Vec<Story> {
+        &self.back_matter
+    }
+
+    /// Returns a copy of the story where every scene and page of matter is transformed by `map`.
+    pub fn map_contents(&self, map: &impl Fn(&str) -> String) -> Self {
+        Self {
+            title: self.title.clone(),
+            kind: self.kind,
+            parts: self
+                .parts
+                .iter()
+                .map(|part| part.map_contents(map))
+                .collect(),
+            contents: self.contents.iter().map(|content| map(content)).collect(),
+            front_matter: self
+                .front_matter
+                .iter()
+                .map(|page| page.map_contents(map))
+                .collect(),
+            back_matter: self
+                .back_matter
+                .iter()
+                .map(|page| page.map_contents(map))
+                .collect(),
+        }
+    }
+
+    /// Selects the chapters of the story by their slash-separated paths. Every path must lead to
+    /// a part or chapter of the story, so a mistyped chapter is never silently left out.
+    pub fn select(&self, chapters: &[String]) -> Result<Self, ConfigError> {
+        let paths: Vec<Vec<&str>> = chapters
+            .iter()
+            .map(|chapter| {
+                chapter
+                    .split('/')
+                    .filter(|segment| !segment.is_empty())
+                    .collect()
+            })
+            .collect();
+
+        let unknown_chapters: Vec<String> = chapters
+            .iter()
+            .zip(&paths)
+            .filter(|(_, path)| !self.contains_path(path))
+            .map(|(chapter, _)| format!("`{}`", chapter))
+            .collect();
+        if !unknown_chapters.is_empty() {
+            return Err(ConfigError::UnknownChapters(unknown_chapters.join(", ")));
+        }
+
+        Ok(self.select_paths(&paths))
+    }
+
+    fn contains_path(&self, path: &[&str]) -> bool {
+        match path.split_first() {
+            Some((title, nested_path)) => self
+                .parts()
+                .iter()
+                .any(|part| part.title() == title && part.contains_path(nested_path)),
+            None => true,
+        }
+    }
+
+    fn select_paths(&self, paths: &[Vec<&str>]) -> Self {
+        let mut story = Self::new(self.title());
+        story.kind = self.kind;
+        story.contents = self.contents.clone();
+        story.front_matter = self.front_matter.clone();
+        story.back_matter = self.back_matter.clone();
+
+        for part in self.parts() {
+            let nested_paths: Vec<Vec<&str>> = paths
+                .iter()
+                .filter(|path| path.first() == Some(&part.title().as_str()))
+                .map(|path| path[1..].to_vec())
+                .collect();
+
+            if nested_paths.is_empty() {
+                continue;
+            }
+
+            if nested_paths.iter().any(|path| path.is_empty()) {
+                story.push_part(part.clone());
+            } else {
+                story.push_part(part.select_paths(&nested_paths));
+            }
+        }
+
+        story
+    }
+
+    /// Restructures the story by the declared kinds of its nested directories, where
+    /// `levels[0]` is the kind of the directories right within this story. The scenes of the
+    /// directories within a chapter, or beyond the declared levels, are appended to the scenes
+    /// of the chapter in order.
+    pub fn with_levels(&self, levels: &[StoryLevel]) -> Self {
+        let mut story = self.clone();
+        story.parts = vec![];
+
+        match levels.split_first() {
+            Some((level, nested_levels)) => {
+                let nested_levels = match level {
+                    StoryLevel::Part => nested_levels,
+                    StoryLevel::Chapter => &[],
+                };
+                for part in &self.parts {
+                    let mut nested_story = part.with_levels(nested_levels);
+                    nested_story.kind = StoryKind::from(*level);
+                    story.parts.push(nested_story);
+                }
+            }
+            None => {
+                for part in &self.parts {
+                    story.contents.extend(part.with_levels(&[]).contents);
+                }
+            }
+        }
+
+        story
+    }
+
+    /// The extensions of the scene files of a story type. Fountain files are only read as the
+    /// scenes of a screenplay, so a stray script in the draft of a prose story is left out.
+    fn scene_extensions(story_type: StoryType) -> &'static [&'static str] {
+        match story_type {
+            StoryType::Prose => &[MAKINILYA_TEXT_EXTENSION],
+            StoryType::Screenplay => &[MAKINILYA_TEXT_EXTENSION, FOUNTAIN_EXTENSION],
+        }
+    }
+
+    pub fn parse(directory: &Directory, story_type: StoryType) -> Self {
         let mut story = Self::new(directory.name());
 
         for item in directory.contents() {
             match item {
                 PathItem::Directory(directory) => {
-                    let nested_story = Self::parse(directory);
+                    let nested_story = Self::parse(directory, story_type);
                     story.push_part(nested_story);
                 }
                 PathItem::File(file) => {
                     if let Some(extension) = &file.extension {
-                        if extension == MAKINILYA_TEXT_EXTENSION {
+                        if Self::scene_extensions(story_type).contains(&extension.as_str()) {
                             let string_content = String::from_utf8_lossy(&file.content);
                             story.push_content(string_content);
                         }
@@ -73,9 +261,201 @@ impl Story {
         story
     }
 
-    pub fn read(path: impl Into<PathBuf>) -> Result<Story, ReaderError> {
+    /// Removes the leading ordinal of a name that's only there to order the directory, e.g.
+    /// `03 - The Storm`, `03. The Storm` or `03_The Storm` becomes `The Storm`. The ordinal
+    /// must be followed by a `.`, `_` or `-`, or by a spaced dash, so a name that starts with a
+    /// number, such as `3 Days Later` or `2001: Arrival`, is kept as it is. A name that would be
+    /// left empty is kept as well.
+    pub fn without_ordinal(name: &str) -> &str {
+        let rest = name.trim_start_matches(|character: char| character.is_ascii_digit());
+        if rest.len() == name.len() {
+            return name;
+        }
+
+        let title = match rest.strip_prefix(['.', '_', '-']) {
+            Some(title) => Some(title),
+            None if rest.starts_with(char::is_whitespace) => rest
+                .trim_start()
+                .strip_prefix('-')
+                .filter(|title| title.starts_with(char::is_whitespace)),
+            None => None,
+        };
+
+        match title.map(str::trim_start) {
+            Some(title) if !title.is_empty() => title,
+            _ => name,
+        }
+    }
+
+    /// Parses every scene of a directory into a page of matter, ordered by the file names. The
+    /// page's title is the file name without its extension and any leading ordinal, e.g.
+    /// `01 - Dedication.mt` is titled `Dedication`.
+    pub fn parse_matter(directory: &Directory) -> Vec<Self> {
+        let mut files: Vec<&File> = directory
+            .contents()
+            .iter()
+            .filter_map(|item| match item {
+                PathItem::File(file)
+                    if file.extension.as_deref() == Some(MAKINILYA_TEXT_EXTENSION) =>
+                {
+                    Some(file)
+                }
+                _ => None,
+            })
+            .collect();
+        files.sort_by(|a, b| compare_names(&a.name, &b.name));
+
+        files
+            .into_iter()
+            .map(|file| {
+                let stem = file
+                    .name
+                    .strip_suffix(&format!(".{}", MAKINILYA_TEXT_EXTENSION))
+                    .unwrap_or(&file.name);
+                let mut page = Self::new(Self::without_ordinal(stem));
+                page.push_content(String::from_utf8_lossy(&file.content));
+                page
+            })
+            .collect()
+    }
+
+    pub fn read(path: impl Into<PathBuf>, story_type: StoryType) -> Result<Story, ReaderError> {
         let directory = Directory::read(path)?;
-        let story = Story::parse(&directory);
+        let mut story = Story::parse(&directory, story_type);
+        story.set_kind(StoryKind::Book);
         Ok(story)
     }
+
+    pub fn read_matter(path: impl Into<PathBuf>) -> Result<Vec<Story>, ReaderError> {
+        let directory = Directory::read(path)?;
+        Ok(Story::parse_matter(&directory))
+    }
+}
+
+#[cfg(test)]
+mod story_tests {
+    use super::*;
+
+    #[test]
+    fn selects_chapters() {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        part_1.push_part(Story::new("Chapter 1"));
+        part_1.push_part(Story::new("Chapter 2"));
+        story.push_part(part_1);
+        story.push_part(Story::new("Part 2"));
+
+        let selected = story.select(&["Part 1/Chapter 2".into()]).unwrap();
+        assert_eq!(selected.parts().len(), 1);
+        assert_eq!(selected.parts()[0].parts().len(), 1);
+        assert_eq!(selected.parts()[0].parts()[0].title(), "Chapter 2");
+
+        let selected = story.select(&["Part 2".into(), "Part 1".into()]).unwrap();
+        assert_eq!(selected.parts().len(), 2);
+        assert_eq!(selected.parts()[0].parts().len(), 2);
+    }
+
+    #[test]
+    fn rejects_unknown_chapters() {
+        let mut story = Story::new("Root");
+        let mut part_1 = Story::new("Part 1");
+        part_1.push_part(Story::new("Chapter 1"));
+        story.push_part(part_1);
+
+        let error = story
+            .select(&[
+                "Part 1/Chapter 1".into(),
+                "Part 1/Chapter 3".into(),
+                "Prat 2".into(),
+            ])
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The chapters `Part 1/Chapter 3`, `Prat 2` are not in the draft."
+        );
+    }
+
+    #[test]
+    fn structures_levels() {
+        let mut story = Story::new("Root");
+        story.set_kind(StoryKind::Book);
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content("Scene 1");
+        let mut drafts = Story::new("Drafts");
+        drafts.push_content("Scene 2");
+        chapter_1.push_part(drafts);
+        story.push_part(chapter_1);
+        assert_eq!(story.parts()[0].kind(), StoryKind::Part);
+
+        let structured = story.with_levels(&[StoryLevel::Chapter]);
+        assert_eq!(structured.kind(), StoryKind::Book);
+        assert_eq!(structured.parts()[0].kind(), StoryKind::Chapter);
+        assert!(structured.parts()[0].parts().is_empty());
+        assert_eq!(
+            structured.parts()[0].contents(),
+            &vec!["Scene 1".to_string(), "Scene 2".to_string()]
+        );
+
+        let flattened = story.with_levels(&[]);
+        assert!(flattened.parts().is_empty());
+        assert_eq!(flattened.contents().len(), 2);
+    }
+
+    #[test]
+    fn parses_scenes_of_story_type() {
+        let mut directory = Directory::new("draft");
+        for name in ["01.mt", "02.fountain"] {
+            directory.push_item(PathItem::File(File {
+                name: name.into(),
+                content: name.as_bytes().to_vec(),
+                extension: name.rsplit_once('.').map(|(_, extension)| extension.into()),
+            }));
+        }
+
+        assert_eq!(
+            Story::parse(&directory, StoryType::Prose).contents(),
+            &vec!["01.mt".to_string()]
+        );
+        assert_eq!(
+            Story::parse(&directory, StoryType::Screenplay)
+                .contents()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn parses_matter() {
+        let mut directory = Directory::new("front_matter");
+        for name in ["02 - Epigraph.mt", "01 - Dedication.mt", "notes.txt"] {
+            directory.push_item(PathItem::File(File {
+                name: name.into(),
+                content: name.as_bytes().to_vec(),
+                extension: name.rsplit_once('.').map(|(_, extension)| extension.into()),
+            }));
+        }
+
+        let matter = Story::parse_matter(&directory);
+        assert_eq!(matter.len(), 2);
+        assert_eq!(matter[0].title(), "Dedication");
+        assert_eq!(
+            matter[0].contents(),
+            &vec!["01 - Dedication.mt".to_string()]
+        );
+        assert_eq!(matter[1].title(), "Epigraph");
+    }
+
+    #[test]
+    fn removes_ordinals() {
+        assert_eq!(Story::without_ordinal("03 - The Storm"), "The Storm");
+        assert_eq!(Story::without_ordinal("03. The Storm"), "The Storm");
+        assert_eq!(Story::without_ordinal("03_The Storm"), "The Storm");
+        assert_eq!(Story::without_ordinal("03-The Storm"), "The Storm");
+        assert_eq!(Story::without_ordinal("3 Days Later"), "3 Days Later");
+        assert_eq!(Story::without_ordinal("1984"), "1984");
+        assert_eq!(Story::without_ordinal("2001: Arrival"), "2001: Arrival");
+        assert_eq!(Story::without_ordinal("3 -2 Degrees"), "3 -2 Degrees");
+        assert_eq!(Story::without_ordinal("01. "), "01. ");
+        assert_eq!(Story::without_ordinal("The Storm"), "The Storm");
+    }
 }
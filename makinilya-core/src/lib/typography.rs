@@ -0,0 +1,333 @@
+#![doc(hidden)]
+
+//! Transforms the punctuation of the interpolated story before it's built.
+//!
+//! The smart transform curls straight quotes by what precedes them, so a quote at the start of a
+//! word opens and any other closes, and an apostrophe within a word is always a closing single
+//! quote. An apostrophe at the start of a word, as in `'twas` or `'em`, can't be told apart from
+//! an opening quote and is curled as one, so it should be written as `’` itself, unless it's
+//! followed by a digit as in `'90s`. A character that's escaped with a backslash is never
+//! transformed, e.g. `\"` is kept as a straight quote and `\--` as two hyphens.
+//!
+//! French text has a narrow no-break space before `;`, `!` and `?`, and a no-break space before
+//! `:` and within guillemets, whether or not the text has a space there.
+//!
+//! The markup of the scenes is never transformed either, so the lines of images and fences, and
+//! the labels of notes such as `[^1]` or `[^1]:`, are kept as they're written.
+
+use makinilya_text::{MakinilyaText, Rule};
+
+use crate::config::TypographyMode;
+
+pub struct Typographer;
+
+impl Typographer {
+    const NO_BREAK_SPACE: char = '\u{a0}';
+    const NARROW_NO_BREAK_SPACE: char = '\u{202f}';
+
+    pub fn transform(text: &str, mode: TypographyMode, language: &str) -> String {
+        match mode {
+            TypographyMode::Smart => Self::smarten(text, language),
+            TypographyMode::Plain => Self::plain(text),
+        }
+    }
+
+    /// Whether each character of the text is part of the markup, i.e. an image or fence line, the
+    /// label of a note's definition, or a reference to a note.
+    fn markup_characters(text: &str) -> Vec<bool> {
+        let mut ranges = vec![];
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\r', '\n']);
+            let markup = MakinilyaText::parse_block(content)
+                .ok()
+                .and_then(|mut pairs| pairs.next())
+                .and_then(|block| block.into_inner().next());
+            let inline_text = match markup {
+                Some(pair) if matches!(pair.as_rule(), Rule::image | Rule::fence) => {
+                    ranges.push(offset..offset + content.len());
+                    None
+                }
+                Some(pair)
+                    if matches!(pair.as_rule(), Rule::note_definition | Rule::block_quote) =>
+                {
+                    let is_definition = pair.as_rule() == Rule::note_definition;
+                    let text = pair
+                        .into_inner()
+                        .find(|pair| pair.as_rule() == Rule::quote_text);
+                    if let (true, Some(text)) = (is_definition, &text) {
+                        ranges.push(offset..offset + text.as_span().start());
+                    }
+                    text.map(|text| (text.as_span().start(), text.as_str()))
+                }
+                _ => Some((0, content)),
+            };
+
+            if let Some((start, inline_text)) = inline_text {
+                let references = MakinilyaText::parse_inline(inline_text)
+                    .ok()
+                    .and_then(|mut pairs| pairs.next())
+                    .into_iter()
+                    .flat_map(|inline| inline.into_inner())
+                    .filter(|pair| pair.as_rule() == Rule::note_reference);
+                for reference in references {
+                    let span = reference.as_span();
+                    ranges.push(offset + start + span.start()..offset + start + span.end());
+                }
+            }
+            offset += line.len();
+        }
+
+        text.char_indices()
+            .map(|(index, _)| ranges.iter().any(|range| range.contains(&index)))
+            .collect()
+    }
+
+    /// Whether a quote after the character opens a quotation.
+    fn opens_quotation(previous: Option<char>) -> bool {
+        match previous {
+            None => true,
+            Some(character) => {
+                character.is_whitespace()
+                    || matches!(
+                        character,
+                        '(' | '[' | '{' | '*' | '—' | '–' | '“' | '‘' | '„' | '«'
+                    )
+            }
+        }
+    }
+
+    fn smarten(text: &str, language: &str) -> String {
+        let primary_language = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let is_french = primary_language == "fr";
+        let (double_open, double_close) = match primary_language.as_str() {
+            "fr" => ('«', '»'),
+            "de" => ('„', '“'),
+            _ => ('“', '”'),
+        };
+
+        let characters: Vec<char> = text.chars().collect();
+        let markup = Self::markup_characters(text);
+        let followed_by = |index: usize, pattern: &str| {
+            pattern.chars().enumerate().all(|(offset, character)| {
+                characters.get(index + offset) == Some(&character)
+                    && markup.get(index + offset) == Some(&false)
+            })
+        };
+        let mut smart = String::with_capacity(text.len());
+        let mut index = 0;
+
+        while index < characters.len() {
+            if markup[index] {
+                smart.push(characters[index]);
+                index += 1;
+                continue;
+            }
+
+            let character = match characters[index] {
+                '\\' => {
+                    smart.push('\\');
+                    smart.extend(characters.get(index + 1));
+                    index += 2;
+                    continue;
+                }
+                '"' if Self::opens_quotation(smart.chars().next_back()) => double_open,
+                '"' => double_close,
+                '\'' if characters
+                    .get(index + 1)
+                    .is_some_and(|next| next.is_ascii_digit()) =>
+                {
+                    '’'
+                }
+                '\'' if Self::opens_quotation(smart.chars().next_back()) => '‘',
+                '\'' => '’',
+                '-' if followed_by(index, "---") => {
+                    index += 2;
+                    '—'
+                }
+                '-' if followed_by(index, "--") => {
+                    index += 1;
+                    '–'
+                }
+                '.' if followed_by(index, "...") => {
+                    index += 2;
+                    '…'
+                }
+                character => character,
+            };
+            index += 1;
+
+            if !is_french {
+                smart.push(character);
+                continue;
+            }
+
+            // French sets guillemets and high punctuation apart with non-breaking spaces.
+            match character {
+                '«' => {
+                    smart.push(character);
+                    while characters.get(index) == Some(&' ') {
+                        index += 1;
+                    }
+                    if characters.get(index).is_some_and(|next| *next != '\n') {
+                        smart.push(Self::NO_BREAK_SPACE);
+                    }
+                }
+                '»' => {
+                    while smart.ends_with(' ') {
+                        smart.pop();
+                    }
+                    if !smart.is_empty() && !smart.ends_with(['\n', Self::NO_BREAK_SPACE]) {
+                        smart.push(Self::NO_BREAK_SPACE);
+                    }
+                    smart.push(character);
+                }
+                ';' | ':' | '!' | '?' => {
+                    let space = match character {
+                        ':' => Self::NO_BREAK_SPACE,
+                        _ => Self::NARROW_NO_BREAK_SPACE,
+                    };
+                    let next = characters.get(index);
+                    if smart.ends_with(' ') {
+                        smart.pop();
+                        smart.push(space);
+                    } else if !smart.is_empty()
+                        && !smart.ends_with(char::is_whitespace)
+                        && !smart.ends_with([';', ':', '!', '?'])
+                        && next.is_none_or(|next| !next.is_alphanumeric() && *next != '/')
+                    {
+                        smart.push(space);
+                    }
+                    smart.push(character);
+                }
+                _ => smart.push(character),
+            }
+        }
+
+        smart
+    }
+
+    fn plain(text: &str) -> String {
+        let no_break_spaces = [' ', Self::NO_BREAK_SPACE, Self::NARROW_NO_BREAK_SPACE];
+        let mut plain = String::with_capacity(text.len());
+        let mut characters = text.chars().zip(Self::markup_characters(text)).peekable();
+
+        while let Some((character, is_markup)) = characters.next() {
+            if is_markup {
+                plain.push(character);
+                continue;
+            }
+
+            match character {
+                '\\' => {
+                    plain.push('\\');
+                    plain.extend(characters.next().map(|(character, _)| character));
+                }
+                '«' => {
+                    plain.push('"');
+                    while characters
+                        .next_if(|(next, is_markup)| !is_markup && no_break_spaces.contains(next))
+                        .is_some()
+                    {}
+                }
+                '»' => {
+                    while plain.ends_with(no_break_spaces) {
+                        plain.pop();
+                    }
+                    plain.push('"');
+                }
+                '“' | '”' | '„' => plain.push('"'),
+                '‘' | '’' | '‚' | '‹' | '›' => plain.push('\''),
+                '—' => plain.push_str("---"),
+                '–' => plain.push_str("--"),
+                '…' => plain.push_str("..."),
+                Self::NO_BREAK_SPACE | Self::NARROW_NO_BREAK_SPACE => plain.push(' '),
+                character => plain.push(character),
+            }
+        }
+
+        plain
+    }
+}
+
+#[cfg(test)]
+mod typography_tests {
+    use super::*;
+
+    #[test]
+    fn smartens_text() {
+        assert_eq!(
+            Typographer::transform(
+                "\"I don't know,\" she said -- 'twice'... (\"Wait\")---\\\"no\\--",
+                TypographyMode::Smart,
+                "en"
+            ),
+            "“I don’t know,” she said – ‘twice’… (“Wait”)—\\\"no\\--"
+        );
+        assert_eq!(
+            Typographer::transform("\"Bonjour\" ! «Oui»", TypographyMode::Smart, "fr-CA"),
+            "«\u{a0}Bonjour\u{a0}»\u{202f}! «\u{a0}Oui\u{a0}»"
+        );
+        assert_eq!(
+            Typographer::transform(
+                "\"Oui!\" Quoi?! Note: 10:30, http://a.fr",
+                TypographyMode::Smart,
+                "fr"
+            ),
+            "«\u{a0}Oui\u{202f}!\u{a0}» Quoi\u{202f}?! Note\u{a0}: 10:30, http://a.fr"
+        );
+        assert_eq!(
+            Typographer::transform("'twas the '90s", TypographyMode::Smart, "en"),
+            "‘twas the ’90s"
+        );
+        assert_eq!(
+            Typographer::transform("\"Ja\"", TypographyMode::Smart, "de"),
+            "„Ja“"
+        );
+    }
+
+    #[test]
+    fn plains_text() {
+        assert_eq!(
+            Typographer::transform(
+                "“I don’t know,” she said – ‘twice’… «\u{a0}Oui\u{a0}»—\\—",
+                TypographyMode::Plain,
+                "en"
+            ),
+            "\"I don't know,\" she said -- 'twice'... \"Oui\"---\\—"
+        );
+    }
+
+    #[test]
+    fn keeps_markup() {
+        let text = "\
+![\"Map\"](images/old--map...png){width=4}
+::: center
+\"Wait\"[^it's--1] -- done.
+> 'Quoted'[^a...b]
+[^it's--1]: \"Note\" -- text.";
+
+        assert_eq!(
+            Typographer::transform(text, TypographyMode::Smart, "en"),
+            "\
+![\"Map\"](images/old--map...png){width=4}
+::: center
+“Wait”[^it's--1] – done.
+> ‘Quoted’[^a...b]
+[^it's--1]: “Note” – text."
+        );
+        assert_eq!(
+            Typographer::transform(
+                "![“Map”](images/old–map….png)\n“Wait”[^‘1’] – done.",
+                TypographyMode::Plain,
+                "en"
+            ),
+            "![“Map”](images/old–map….png)\n\"Wait\"[^‘1’] -- done."
+        );
+    }
+}
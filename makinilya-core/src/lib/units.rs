@@ -55,7 +55,7 @@ impl From<Twip> for i32 {
 
 impl From<Twip> for f32 {
     fn from(value: Twip) -> Self {
-        value.0 as f32
+        value.0
     }
 }
 
@@ -63,27 +63,18 @@ impl PartialEq<f32> for Twip {
     fn eq(&self, other: &f32) -> bool {
         self.0 == *other
     }
-    fn ne(&self, other: &f32) -> bool {
-        self.0 != *other
-    }
 }
 
 impl PartialEq for Twip {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
-    fn ne(&self, other: &Self) -> bool {
-        self.0 != other.0
-    }
 }
 
 impl PartialEq<Twip> for f32 {
     fn eq(&self, other: &Twip) -> bool {
         *self == other.0
     }
-    fn ne(&self, other: &Twip) -> bool {
-        *self != other.0
-    }
 }
 
 /// As the name suggests, this is half of a point. It is 1/144 of an inch.
@@ -111,25 +102,40 @@ impl PartialEq for HalfPoint {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
-    fn ne(&self, other: &Self) -> bool {
-        self.0 != other.0
-    }
 }
 
 impl PartialEq<f32> for HalfPoint {
     fn eq(&self, other: &f32) -> bool {
         self.0 == *other
     }
-    fn ne(&self, other: &f32) -> bool {
-        self.0 != *other
-    }
 }
 
 impl PartialEq<HalfPoint> for f32 {
     fn eq(&self, other: &HalfPoint) -> bool {
         *self == other.0
     }
-    fn ne(&self, other: &HalfPoint) -> bool {
-        *self != other.0
+}
+
+/// English Metric Unit, which measures the size of drawings such as images. It is 1/914400 of
+/// an inch.
+pub struct Emu(f32);
+
+impl Emu {
+    const INCH_TO_EMU: f32 = 914400.0;
+
+    pub fn from_inch(value: f32) -> Self {
+        Self(value * Self::INCH_TO_EMU)
+    }
+}
+
+impl From<Emu> for u32 {
+    fn from(value: Emu) -> Self {
+        value.0.round() as u32
+    }
+}
+
+impl Debug for Emu {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
@@ -0,0 +1,103 @@
+#![doc(hidden)]
+
+//! Counts the words of the narrative.
+//!
+//! A word is a run of characters that's delimited by whitespace, em-dashes (`—`), en-dashes
+//! (`–`), or consecutive hyphens (`--`). Runs that do not contain any letter or digit, such as a
+//! stray `#` or `—`, are not words. Consequently, hyphenated compounds (`well-known`) and
+//! numbers (`1,237` or `3.5`) are counted as single words, while `word—word` is two.
+//!
+//! Only the paragraphs of the story are counted, without their inline markup or notes. The
+//! headings, images, scene breaks and the pages of front and back matter are not counted.
+
+use crate::builder::document::{Block, ManuscriptDocument, Span};
+
+pub struct WordCount;
+
+impl WordCount {
+    const DASHES: [char; 2] = ['—', '–'];
+
+    fn is_delimiter(character: char, next_character: Option<&char>) -> bool {
+        character.is_whitespace()
+            || Self::DASHES.contains(&character)
+            || (character == '-' && next_character == Some(&'-'))
+    }
+
+    pub fn count_text(text: &str) -> u32 {
+        let mut count = 0;
+        let mut has_word = false;
+        let mut characters = text.chars().peekable();
+
+        while let Some(character) = characters.next() {
+            if Self::is_delimiter(character, characters.peek()) {
+                if character == '-' {
+                    characters.next_if_eq(&'-');
+                }
+                if has_word {
+                    count += 1;
+                }
+                has_word = false;
+            } else if character.is_alphanumeric() {
+                has_word = true;
+            }
+        }
+
+        if has_word {
+            count += 1;
+        }
+
+        count
+    }
+
+    pub fn count_document(document: &ManuscriptDocument) -> u32 {
+        document
+            .chapters()
+            .iter()
+            .filter(|chapter| !chapter.matter)
+            .flat_map(|chapter| chapter.blocks)
+            .map(|block| match block {
+                Block::Paragraph(text)
+                | Block::FirstParagraph(text)
+                | Block::CenteredParagraph(text)
+                | Block::BlockQuote(text) => Self::count_text(&Span::plain(text)),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod word_count_tests {
+    use super::*;
+    use crate::{builder::ManuscriptBuilderLayout, story::Story};
+
+    #[test]
+    fn counts_words() {
+        assert_eq!(WordCount::count_text("Hello, my name is Core."), 5);
+        assert_eq!(WordCount::count_text("  Hello\n\nworld  "), 2);
+        assert_eq!(WordCount::count_text("A well-known fact."), 3);
+        assert_eq!(WordCount::count_text("Wait—what? Wait -- what?"), 4);
+        assert_eq!(
+            WordCount::count_text("It cost 1,237 pesos, or 3.5 days."),
+            7
+        );
+        assert_eq!(WordCount::count_text("#\n* * *\n—"), 0);
+    }
+
+    #[test]
+    fn counts_words_of_paragraphs() {
+        let mut story = Story::new("Root");
+        let mut chapter_1 = Story::new("Chapter 1");
+        chapter_1.push_content(
+            "![A map of the sea](maps/sea.png)\nI have *never*^[Not once.] been to the sea.",
+        );
+        chapter_1.push_content("And I still haven't.");
+        story.push_part(chapter_1);
+        let mut dedication = Story::new("Dedication");
+        dedication.push_content("For the sea.");
+        story.push_front_matter(dedication);
+
+        let document = ManuscriptDocument::from_story(&story, &ManuscriptBuilderLayout::default());
+        assert_eq!(WordCount::count_document(&document), 11);
+    }
+}
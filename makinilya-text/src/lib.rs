@@ -25,7 +25,28 @@ pub struct MakinilyaText;
 impl MakinilyaText {
     /// parses source string into pest's token pairs.
     pub fn parse(source: &str) -> Result<Pairs<'_, Rule>, Error> {
-        GrammarParser::parse(Rule::makinilya, source).map_err(|error| Self::map_parser_error(error))
+        GrammarParser::parse(Rule::makinilya, source).map_err(Self::map_parser_error)
+    }
+
+    /// parses an interpolated paragraph into its inline markup, i.e. `*emphasis*`, inline notes
+    /// such as `^[note text]`, references to notes such as `[^1]`, and escaped characters such as
+    /// `\*`.
+    pub fn parse_inline(source: &str) -> Result<Pairs<'_, Rule>, Error> {
+        GrammarParser::parse(Rule::inline, source).map_err(Self::map_parser_error)
+    }
+
+    /// Whether an interpolated line is an explicit scene break, i.e. a line of only three or
+    /// more asterisks such as `***` or `* * *`.
+    pub fn is_scene_break(line: &str) -> bool {
+        GrammarParser::parse(Rule::scene_break, line).is_ok()
+    }
+
+    /// parses an interpolated line into its block markup, i.e. a `> ` block quote, a fence such
+    /// as `::: center` that opens or closes a block, the definition of a note such as
+    /// `[^1]: note text`, an image such as `![alt](path){width=4}`, or the text of any other
+    /// line.
+    pub fn parse_block(line: &str) -> Result<Pairs<'_, Rule>, Error> {
+        GrammarParser::parse(Rule::block, line).map_err(Self::map_parser_error)
     }
 
     fn map_parser_error<R>(error: pest::error::Error<R>) -> Error
@@ -70,5 +91,139 @@ mod parser_tests {
         assert!(file.is_ok());
         let file = GrammarParser::parse(Rule::makinilya, "Hello. My name is {{ name.long }}.");
         assert!(file.is_ok());
+
+        let expressions: Vec<&str> = MakinilyaText::parse("{{ name }} walks in.")
+            .unwrap()
+            .next()
+            .unwrap()
+            .into_inner()
+            .map(|pair| pair.as_str())
+            .collect();
+        assert_eq!(expressions, vec!["{{ name }}", " walks in.", ""]);
+    }
+
+    #[test]
+    fn parses_inline() {
+        let rules = |source| -> Vec<Rule> {
+            MakinilyaText::parse_inline(source)
+                .unwrap()
+                .next()
+                .unwrap()
+                .into_inner()
+                .map(|pair| pair.as_rule())
+                .collect()
+        };
+
+        assert_eq!(
+            rules("I *never* said that."),
+            vec![
+                Rule::plain_text,
+                Rule::emphasis,
+                Rule::plain_text,
+                Rule::EOI
+            ]
+        );
+        assert_eq!(
+            rules("2 * 3 \\* 4"),
+            vec![
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::escaped_character,
+                Rule::plain_text,
+                Rule::EOI
+            ]
+        );
+        assert_eq!(
+            rules("Tea^[Or *coffee* \\]] and cake[^cake] ^[ [^"),
+            vec![
+                Rule::plain_text,
+                Rule::note,
+                Rule::plain_text,
+                Rule::note_reference,
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::plain_text,
+                Rule::EOI
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_blocks() {
+        let rules = |source| -> Vec<(Rule, &str)> {
+            MakinilyaText::parse_block(source)
+                .unwrap()
+                .next()
+                .unwrap()
+                .into_inner()
+                .flat_map(|pair| {
+                    let mut pairs = vec![(pair.as_rule(), pair.as_str())];
+                    pairs.extend(
+                        pair.into_inner()
+                            .map(|pair| (pair.as_rule(), pair.as_str())),
+                    );
+                    pairs
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            rules("> Dear Tom,"),
+            vec![
+                (Rule::block_quote, "> Dear Tom,"),
+                (Rule::quote_text, "Dear Tom,"),
+                (Rule::EOI, "")
+            ]
+        );
+        assert_eq!(
+            rules("::: center "),
+            vec![
+                (Rule::fence, "::: center "),
+                (Rule::fence_name, "center"),
+                (Rule::EOI, "")
+            ]
+        );
+        assert_eq!(rules(":::"), vec![(Rule::fence, ":::"), (Rule::EOI, "")]);
+        assert_eq!(
+            rules("::: not a fence"),
+            vec![(Rule::line_text, "::: not a fence"), (Rule::EOI, "")]
+        );
+        assert_eq!(
+            rules("[^1]: A *note*."),
+            vec![
+                (Rule::note_definition, "[^1]: A *note*."),
+                (Rule::note_label, "1"),
+                (Rule::quote_text, "A *note*."),
+                (Rule::EOI, "")
+            ]
+        );
+        assert_eq!(
+            rules("![A map](images/map.png){width=4 align=left}"),
+            vec![
+                (Rule::image, "![A map](images/map.png){width=4 align=left}"),
+                (Rule::image_alt, "A map"),
+                (Rule::image_path, "images/map.png"),
+                (Rule::image_option, "width=4"),
+                (Rule::image_option, "align=left"),
+                (Rule::EOI, "")
+            ]
+        );
+        assert_eq!(
+            rules("![](map.png) and text"),
+            vec![(Rule::line_text, "![](map.png) and text"), (Rule::EOI, "")]
+        );
+    }
+
+    #[test]
+    fn parses_scene_breaks() {
+        assert!(MakinilyaText::is_scene_break("***"));
+        assert!(MakinilyaText::is_scene_break(" * * * "));
+        assert!(MakinilyaText::is_scene_break("*****"));
+        assert!(!MakinilyaText::is_scene_break("**"));
+        assert!(!MakinilyaText::is_scene_break("*emphasis*"));
+        assert!(!MakinilyaText::is_scene_break("# * *"));
     }
 }